@@ -2,6 +2,9 @@
 // Imports
 use crate::log;
 use crate::troopers::TrooperClass;
+use crate::combat::{DamageEvent, DamageProfile, Joker, Mitigated, RangeMods, RangeProfile};
+use crate::hive::Position;
+use crate::utils::SafeSub;
 use rand::prelude::IndexedRandom;
 
 // TODO: Remove Effect Matchup in GearStats (AFTER ARMORY) (See related notes down by GearStats)
@@ -11,11 +14,51 @@ use rand::prelude::IndexedRandom;
 
 // GENERAL ARMORY DECLARATIONS
 
-#[derive(Default, Debug, Copy, Clone)]
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Distance { Far, #[default] Normal, Near, Close }
+
+impl Distance {
+    /// Furthest tile count that still falls inside this band.
+    pub fn max_tiles(&self) -> u32 {
+        match self {
+            Distance::Close => 1,
+            Distance::Near => 3,
+            Distance::Normal => 6,
+            Distance::Far => u32::MAX,
+        }
+    }
+
+    /// 0 for Close up to 3 for Far.
+    pub fn band(&self) -> u8 {
+        match self {
+            Distance::Close => 0,
+            Distance::Near => 1,
+            Distance::Normal => 2,
+            Distance::Far => 3,
+        }
+    }
+
+    pub fn from_tiles(tiles: u32) -> Distance {
+        [Distance::Close, Distance::Near, Distance::Normal].into_iter()
+            .find(|d| tiles <= d.max_tiles())
+            .unwrap_or(Distance::Far)
+    }
+}
+
+impl Area {
+    /// Blast radius in tiles around the impact point.
+    pub fn radius(&self) -> u32 {
+        match self {
+            Area::Immediate => 1,
+            Area::Neighbors => 2,
+        }
+    }
+}
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 enum Usage { Limited(u32), #[default] Unlimited }
 enum EquipmentType { Weapon, Gear }
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ActionCost { Action, Free }
 enum EquipmentID {
     WeaponID(WeaponID),
     GearID(GearID),
@@ -25,14 +68,15 @@ enum EquipmentID {
 
 // ============ EFFECTS & TARGETING =================
 
-#[derive(Default, Debug, Copy, Clone)]
-enum DamageType { #[default] Ballistic, Energy, Explosive, Corrosive, Burn, Physical, Chemical, Repair, Healing }
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DamageType { #[default] Ballistic, Energy, Explosive, Corrosive, Burn, Physical, Chemical, Repair, Healing }
 
 #[derive(Clone, Debug, Copy)]
 pub enum TargetType { Itself, Ally, Enemy, Area, All }
 #[derive(Clone, Debug, Copy)]
 pub enum Area { Immediate, Neighbors }
 #[derive(Clone, Debug, Copy)]
+#[allow(clippy::enum_variant_names)]
 pub enum ReloadPenaltyType { AfterMove, AfterFire, AfterSpecial }
 
 struct TargetStats {
@@ -76,7 +120,7 @@ pub enum Effect {
     MarkTarget,                                     // Reveal traits/stats
     RevealTraits,                                   // Full enemy scan
     ActionRefill,                                   // Use again immediately
-    Cloak { turns: u8 },                            // Untargetable
+    Cloak { turns: u8 },                            // Bugs look past the wearer
     Decoy { duration: u8 },                         // Place decoy to draw aggro
     HoloDouble { turns: u8 },                       // Acts twice, movement penalty after
     Immobilize { turns: u8 },                       // Freeze target
@@ -121,16 +165,172 @@ pub enum EquipmentFlaw {
     NoEffectIfTargetAtMax,
     NoEffectOnElite,
     OnlyForBeltFed,
+    BreaksOnAttack, // Effect drops the moment the user attacks
     // ...any other specific limitation
 
     Custom(&'static str), // fallback for one-off/odd-ball flaws
 }
 
+impl ActionCost {
+    fn from_char(c: char) -> Option<Self> {
+        match c {
+            'A' => Some(ActionCost::Action),
+            'F' => Some(ActionCost::Free),
+            _ => None,
+        }
+    }
+}
+
+impl Effect {
+    /// Who an effect lands on when nothing more specific is known about the user's intent.
+    pub fn target_type(&self) -> TargetType {
+        use Effect::*;
+        match *self {
+            Heal { target, .. } | CleanseDebuffs { target } => target,
+            BuffAP { area: Some(_), .. } | Stun { area: Some(_), .. } | AoE { .. } => TargetType::Area,
+            BuffAP { turns: 0, .. } => TargetType::Ally,
+            Burn { aoe: true, .. } => TargetType::Area,
+            BuffAP { .. } | BuffMove { .. } | BuffAccuracy { .. } | Cloak { .. } | HoloDouble { .. }
+            | QuickDraw | InfiniteAmmo | RecoverAmmoOnCrit | AlwaysSilent | ActionRefill | IgnoreArmor
+            | Decoy { .. } | Trap { .. } | AggroPull { .. } => TargetType::Itself,
+            Regen { .. } | Revive { .. } => TargetType::Ally,
+            _ => TargetType::Enemy,
+        }
+    }
+
+    pub fn area(&self) -> Option<Area> {
+        match self {
+            Effect::Stun { area, .. } | Effect::BuffAP { area, .. } => *area,
+            Effect::AoE { aoe, .. } => Some(*aoe),
+            Effect::Burn { aoe: true, .. } => Some(Area::Immediate),
+            _ => None,
+        }
+    }
+
+    /// Upfront damage the effect deals on landing: blasts and arcs go straight through armor.
+    pub fn damage_profile(&self) -> Option<DamageProfile> {
+        match *self {
+            Effect::AoE { dmg, .. } => Some(DamageProfile::true_damage(DamageType::Explosive, dmg)),
+            Effect::ChainDamage { dmg, .. } => Some(DamageProfile::true_damage(DamageType::Energy, dmg)),
+            _ => None,
+        }
+    }
+
+    /// Percent of HP damage this effect lets through armor.
+    pub fn pierce(&self) -> u8 {
+        match self {
+            Effect::ArmorPierce { percent } => (*percent).min(100),
+            Effect::IgnoreArmor => 100,
+            _ => 0,
+        }
+    }
+
+    /// Whether the effect helps whoever it lands on (heals, buffs, cleanses).
+    pub fn is_beneficial(&self) -> bool {
+        use Effect::*;
+        matches!(self,
+            BuffAP { .. } | BuffMove { .. } | BuffAccuracy { .. } | Heal { .. } | Regen { .. } | Revive { .. }
+            | CleanseDebuffs { .. } | QuickDraw | InfiniteAmmo | RecoverAmmoOnCrit | AlwaysSilent | ActionRefill
+            | Cloak { .. } | Decoy { .. } | HoloDouble { .. } | Trap { .. } | AggroPull { .. } | IgnoreArmor
+        )
+    }
+
+    /// Scales the healing and armor-repair amounts by the user's output multipliers.
+    pub fn scale_support(self, heal: f32, repair: f32) -> Effect {
+        let by = |x: u32, m: f32| (x as f32 * m).round() as u32;
+        match self {
+            Effect::Heal { hp, target } => Effect::Heal { hp: by(hp, heal), target },
+            Effect::Regen { hp_per_turn, turns } => Effect::Regen { hp_per_turn: by(hp_per_turn, heal), turns },
+            Effect::Revive { hp } => Effect::Revive { hp: by(hp, heal) },
+            Effect::BuffAP { ap, turns: 0, area } if ap > 0 => Effect::BuffAP { ap: (ap as f32 * repair).round() as i32, turns: 0, area },
+            other => other,
+        }
+    }
+
+    /// Turns the effect lingers for once applied. Zero means it resolves instantly.
+    pub fn duration(&self) -> u8 {
+        use Effect::*;
+        match *self {
+            Stun { turns, .. } | Bleed { turns, .. } | Burn { turns, .. } | Poison { turns, .. }
+            | Corrode { turns, .. } | Suppress { turns, .. } | BuffAP { turns, .. } | BuffMove { turns, .. }
+            | BuffAccuracy { turns, .. } | Regen { turns, .. } | Cloak { turns } | HoloDouble { turns }
+            | Immobilize { turns } | Pacify { turns } | Confuse { turns } | Blind { turns, .. }
+            | AggroPull { turns } => turns,
+            Decoy { duration } | Trap { duration } => duration,
+            IgnoreArmor => 2,
+            _ => 0,
+        }
+    }
+}
+
+/// An effect that has landed on a Trooper or Bug and is still counting down.
+#[derive(Copy, Clone, Debug)]
+pub struct ActiveEffect {
+    pub effect: Effect,
+    pub turns_left: u8,
+    /// The wearable keeping it up. Worn effects don't count down; they last until the gear comes off.
+    pub worn: Option<&'static str>,
+}
+
+impl ActiveEffect {
+    pub fn new(effect: Effect) -> Option<Self> {
+        let turns_left = effect.duration();
+        (turns_left > 0).then_some(ActiveEffect { effect, turns_left, worn: None })
+    }
+
+    pub fn worn(effect: Effect, gear: &'static str) -> Self {
+        ActiveEffect { effect, turns_left: effect.duration().max(1), worn: Some(gear) }
+    }
+
+    pub fn is_debuff(&self) -> bool { !self.effect.is_beneficial() }
+
+    /// Sum of accuracy buffs and penalties, as a fraction (10 = +0.10).
+    pub fn accuracy_delta(effects: &[ActiveEffect]) -> f32 {
+        effects.iter()
+            .map(|e| match e.effect {
+                Effect::BuffAccuracy { acc, .. } => acc as f32,
+                Effect::Suppress { acc_penalty, .. } | Effect::Blind { acc_penalty, .. } => -(acc_penalty.abs() as f32),
+                _ => 0.0,
+            })
+            .sum::<f32>() / 100.0
+    }
+
+    /// Counts every timed effect down by one turn, returning what the owner gains and loses this tick.
+    pub fn tick_all(effects: &mut Vec<ActiveEffect>) -> EffectTick {
+        let mut tick = EffectTick::default();
+        for e in effects.iter_mut() {
+            match e.effect {
+                Effect::Regen { hp_per_turn, .. } => tick.heal += hp_per_turn,
+                Effect::Bleed { dmg, .. } | Effect::Burn { dmg, .. } => tick.hp_dmg += dmg,
+                Effect::Poison { dmg, stacks, .. } => tick.hp_dmg += dmg * stacks.max(1) as u32,
+                Effect::Corrode { dmg, stacks, .. } => tick.ap_dmg += dmg * stacks.max(1) as u32,
+                _ => {},
+            }
+            if e.worn.is_some() { continue; }
+            e.turns_left -= 1;
+            if let (0, Effect::BuffAP { ap, .. }) = (e.turns_left, e.effect) {
+                tick.expired_ap += ap;
+            }
+        }
+        effects.retain(|e| e.turns_left > 0);
+        tick
+    }
+}
+
+#[derive(Default, Copy, Clone, Debug)]
+pub struct EffectTick {
+    pub heal: u32,
+    pub hp_dmg: u32,
+    pub ap_dmg: u32,
+    pub expired_ap: i32,
+}
+
 //
 
 //  =================== WEAPONS ========================
 // Declarations
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[allow(clippy::upper_case_acronyms)]
 enum WeaponID { #[default] Minigun, Chaingun, ScopedRifle, PulseSMG, MagShellCannon, VenomSpiker, Flamethrower, SlugCannon, MarksmanCarbine, Railgun, Crossbolt, Spikeshot, AntigenBeam, IonScattergun, AssaultRifle, SMG, RepeaterBow, AutoPistol, SawedOffShotgun, PulsePistol, Spikeling, MicroGrenadeLauncher, HandCannon, Syringer, PlasmaDerringer, BackupRevolver, LightSMG, CombatKnife, PowerMace, ShockBlade, Cleaver, InjectorGauntlet, DoomWrench, AspLash, ArcGauntlet, MonofilamentBlade, TacticalBaton  }
 
 static ALL_WEAPON_IDS: &[WeaponID] = &[
@@ -932,6 +1132,11 @@ static WEAPON_STATS: &[WeaponStats] = &[
     },
 ];
 
+// Drawbacks baked into specific weapons.
+static WEAPON_FLAWS: &[(WeaponID, EquipmentFlaw)] = &[
+    (WeaponID::Flamethrower, EquipmentFlaw::BurnsCover),
+];
+
 #[derive(Default, Copy, Clone, Debug)]
 pub struct Weapon {
     id: WeaponID,
@@ -946,20 +1151,72 @@ impl Weapon {
         let info = Armory::get_weapon_info(id);
         let stats = Armory::get_weapon_stats(id);
         
+        let flaw = WEAPON_FLAWS.iter().find(|(wid, _)| *wid == id).map(|(_, f)| *f);
+
         Weapon {
             id,
             info,
             stats,
             effect: None,
-            flaw: None,
+            flaw,
         }
     }
 
-    pub fn damage(&self) -> (u32, u32, u32) {
-        let (dmg, hp_dmg, ap_dmg) = (self.stats.dmg, self.stats.hp_dmg, self.stats.ap_dmg);
-        (dmg, hp_dmg, ap_dmg)
+    pub fn has_damage_type(&self, damage_type: DamageType) -> bool {
+        self.stats.damage_type.contains(&damage_type)
+    }
+
+    pub fn burns_cover(&self) -> bool { matches!(self.flaw, Some(EquipmentFlaw::BurnsCover)) }
+
+    /// How far a shot carries through the hive. Blades and bows barely register, explosives shake
+    /// the walls, and automatic fire gets louder with rate of fire.
+    pub fn noise(&self) -> f32 {
+        use WeaponID::*;
+        match (self.id, self.info.r#type) {
+            (CombatKnife | TacticalBaton | MonofilamentBlade | Crossbolt | RepeaterBow | Syringer | AspLash, _) => 1.0,
+            (_, WeaponType::Melee) => 2.0,
+            _ if self.has_damage_type(DamageType::Explosive) => 8.0,
+            _ if self.has_damage_type(DamageType::Ballistic) => (3 + self.stats.rof).min(7) as f32,
+            _ => 3.0,
+        }
     }
 
+    /// Raw impact (`dmg`) lands on the HP channel alongside `hp_dmg`.
+    pub fn damage_profile(&self) -> DamageProfile {
+        DamageProfile::new(self.damage_type(), self.stats.dmg + self.stats.hp_dmg, self.stats.ap_dmg, self.pierce())
+    }
+
+    /// First damage type the weapon actually hurts with (skips Repair/Healing tags).
+    pub fn damage_type(&self) -> DamageType {
+        self.stats.damage_type.iter()
+            .copied()
+            .find(|d| !matches!(d, DamageType::Repair | DamageType::Healing))
+            .unwrap_or_default()
+    }
+
+    pub fn pierce(&self) -> u8 {
+        self.effect.as_ref().map_or(0, Effect::pierce)
+    }
+
+    pub fn name(&self) -> &'static str { self.info.name }
+
+    pub fn range(&self) -> Distance { self.stats.range }
+
+    pub fn range_profile(&self) -> RangeProfile {
+        match (self.info.r#type, self.id) {
+            (WeaponType::Melee, _) => RangeProfile::Melee,
+            (_, WeaponID::SawedOffShotgun | WeaponID::IonScattergun) => RangeProfile::Scattershot,
+            (_, WeaponID::ScopedRifle | WeaponID::Railgun) => RangeProfile::Marksman,
+            _ => RangeProfile::Standard,
+        }
+    }
+
+    pub fn range_mods(&self, tiles: u32) -> RangeMods {
+        Joker::range_mods(self.stats.range, self.range_profile(), tiles)
+    }
+
+    pub fn in_range(&self, tiles: u32) -> bool { self.range_mods(tiles).usable }
+
     pub fn accuracy(&self) -> f32 {
         self.stats.accuracy_delta
     }
@@ -1357,6 +1614,56 @@ static GEAR_RESTRICTIONS: &[GearRestrictions] = &[
     GearRestrictions { id: GearID::TrapKit,              classes: None },
 ];
 
+/// Flat stat changes worn gear grants for as long as it stays intact.
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+pub struct StatModifier {
+    pub hp: i32,
+    pub ap: i32,
+    pub dmg_mod: f32,
+    pub accuracy: f32,
+    pub agility: f32,
+}
+
+impl std::ops::Add for StatModifier {
+    type Output = StatModifier;
+
+    fn add(self, rhs: StatModifier) -> StatModifier {
+        StatModifier {
+            hp: self.hp + rhs.hp,
+            ap: self.ap + rhs.ap,
+            dmg_mod: self.dmg_mod + rhs.dmg_mod,
+            accuracy: self.accuracy + rhs.accuracy,
+            agility: self.agility + rhs.agility,
+        }
+    }
+}
+
+impl std::ops::Neg for StatModifier {
+    type Output = StatModifier;
+
+    fn neg(self) -> StatModifier {
+        StatModifier { hp: -self.hp, ap: -self.ap, dmg_mod: -self.dmg_mod, accuracy: -self.accuracy, agility: -self.agility }
+    }
+}
+
+// Penalties a wearable carries on top of whatever its effect grants.
+static WEARABLE_TRADEOFFS: &[(GearID, StatModifier)] = &[
+    (GearID::ReinforcedPlating, StatModifier { hp: 0, ap: 0, dmg_mod: 0.0, accuracy: 0.0, agility: -0.10 }),
+];
+
+// Threat a piece of gear shifts onto (or off) its user, on top of its flaw.
+static GEAR_THREAT: &[(GearID, f32)] = &[
+    (GearID::ChitinBait, -30.0),
+];
+
+// How many chambers ahead a scan or sonar ping can see into.
+static GEAR_SCAN_RANGE: &[(GearID, usize)] = &[
+    (GearID::SensorNode, 1),
+    (GearID::EchoBeacon, 1),
+    (GearID::EchoPulse, 2),
+    (GearID::HiveScanner, 2),
+];
+
 // TODO: Go over all of GearStats and GEAR_STATS once effects have actually been implemented. The
 // effects and flaws and everything shouldn't even be in this struct, they should be in the base
 // Gear struct, although again there's no way to set it until the Armory's been set up.
@@ -1416,7 +1723,7 @@ static GEAR_STATS: &[GearStats] = &[
         unlimited:      true,
         action_cost:    Some('F'),
         effect:         Some(Effect::Cloak { turns: 1 }),
-        flaw:           Some(EquipmentFlaw::Custom("Disables if attacking")),
+        flaw:           Some(EquipmentFlaw::BreaksOnAttack),
     },
     GearStats {
         id:             GearID::GrappleLauncher,
@@ -1696,6 +2003,22 @@ static GEAR_STATS: &[GearStats] = &[
     },
 ];
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum GearUseError { Passive, Depleted, OnCooldown(u8), SpentForRun, NoActionLeft, NoEffect }
+
+/// What a piece of gear did when it was used, handed to Overwatch to resolve against targets.
+#[derive(Debug, Copy, Clone)]
+pub struct GearActivation {
+    pub name: &'static str,
+    pub effect: Effect,
+    pub target: TargetType,
+    pub cost: Option<ActionCost>,
+    pub flaw: Option<EquipmentFlaw>,
+    pub deploy: Option<DeployableStats>,
+    pub threat: f32,
+    pub scan: Option<usize>,
+}
+
 #[derive(Default, Clone, Copy, Debug)]
 pub struct Gear {
     id: GearID,
@@ -1703,40 +2026,372 @@ pub struct Gear {
     stats: GearStats,
     effect: Option<Effect>,
     flaw: Option<EquipmentFlaw>,
+    uses_left: Usage,
+    cooldown: u8,
+    spent_for_run: bool,
+    destroyed: bool,
 }
 
 impl Gear {
     fn new(id: GearID) -> Self {
         let info = Armory::get_gear_info(id);
         let stats = Armory::get_gear_stats(id);
+        let uses_left = match (stats.unlimited, stats.uses) {
+            (false, Some(n)) => Usage::Limited(n),
+            _ => Usage::Unlimited,
+        };
 
         Gear {
             id,
             info,
             stats,
-            effect: None,
-            flaw: None,
+            effect: stats.effect,
+            flaw: stats.flaw,
+            uses_left,
+            cooldown: 0,
+            spent_for_run: false,
+            destroyed: false,
         }
     }
+
+    pub fn name(&self) -> &'static str { self.info.name }
+
+    pub fn action_cost(&self) -> Option<ActionCost> {
+        self.stats.action_cost.and_then(ActionCost::from_char)
+    }
+
+    pub fn is_passive(&self) -> bool { self.info.r#type == GearType::Wearable }
+
+    pub fn is_destroyed(&self) -> bool { self.destroyed }
+
+    pub fn flaw(&self) -> Option<EquipmentFlaw> { self.flaw }
+
+    /// Stat changes this item grants while worn. Permanent AP buffs become flat armor.
+    pub fn passive_modifier(&self) -> StatModifier {
+        if !self.is_passive() || self.destroyed {
+            return StatModifier::default();
+        }
+        let from_effect = match self.effect {
+            Some(Effect::BuffAP { ap, turns: 0, .. }) => StatModifier { ap, ..Default::default() },
+            _ => StatModifier::default(),
+        };
+        let tradeoff = WEARABLE_TRADEOFFS.iter()
+            .find(|(id, _)| *id == self.id)
+            .map(|(_, m)| *m)
+            .unwrap_or_default();
+        from_effect + tradeoff
+    }
+
+    /// A lingering effect a wearable keeps on its owner (e.g. Nano-Patch regen).
+    pub fn passive_effect(&self) -> Option<Effect> {
+        if !self.is_passive() || self.destroyed {
+            return None;
+        }
+        self.effect.filter(|e| e.duration() > 0)
+    }
+
+    pub fn grants_armor(&self) -> bool { self.passive_modifier().ap > 0 }
+
+    pub fn uses_left(&self) -> Option<u32> {
+        match self.uses_left {
+            Usage::Limited(n) => Some(n),
+            Usage::Unlimited => None,
+        }
+    }
+
+    pub fn effect(&self) -> Option<Effect> { self.effect }
+
+    pub fn breaks_on_attack(&self) -> bool { matches!(self.flaw, Some(EquipmentFlaw::BreaksOnAttack)) }
+
+    /// Checks everything short of the trooper's action budget.
+    pub fn can_use(&self) -> Result<(), GearUseError> {
+        if self.is_passive() { return Err(GearUseError::Passive); }
+        if self.destroyed { return Err(GearUseError::Depleted); }
+        if self.spent_for_run { return Err(GearUseError::SpentForRun); }
+        if self.cooldown > 0 { return Err(GearUseError::OnCooldown(self.cooldown)); }
+        if self.uses_left == Usage::Limited(0) { return Err(GearUseError::Depleted); }
+        if self.effect.is_none() { return Err(GearUseError::NoEffect); }
+        Ok(())
+    }
+
+    /// Spends a use and arms any flaw that limits the next one.
+    pub fn activate(&mut self) -> Result<GearActivation, GearUseError> {
+        self.can_use()?;
+        let effect = self.effect.ok_or(GearUseError::NoEffect)?;
+
+        if let Usage::Limited(n) = self.uses_left {
+            self.uses_left = Usage::Limited(n - 1);
+        }
+        match self.flaw {
+            // +1 because the owner's next begin_turn ticks it straight back down
+            Some(EquipmentFlaw::Cooldown { turns }) => self.cooldown = turns + 1,
+            Some(EquipmentFlaw::OneUsePerRun) => self.spent_for_run = true,
+            _ => {},
+        }
+
+        Ok(GearActivation {
+            name: self.info.name,
+            effect,
+            target: effect.target_type(),
+            cost: self.action_cost(),
+            flaw: self.flaw,
+            deploy: self.deployable(),
+            threat: self.threat_on_use(),
+            scan: self.scan_range(),
+        })
+    }
+
+    /// How much noise using this makes: loud flaws draw bugs, bait pushes them elsewhere.
+    pub fn threat_on_use(&self) -> f32 {
+        let flaw = match self.flaw {
+            Some(EquipmentFlaw::AttractsAggro) => 25.0,
+            Some(EquipmentFlaw::VisibleToEnemies) => 10.0,
+            _ => 0.0,
+        };
+        let shift = GEAR_THREAT.iter().find(|(id, _)| *id == self.id).map_or(0.0, |(_, t)| *t);
+        flaw + shift
+    }
+
+    /// Uses missing from a full load; zero for unlimited gear.
+    pub fn uses_missing(&self) -> u32 {
+        match (self.uses_left, self.stats.uses) {
+            (Usage::Limited(n), Some(max)) if !self.destroyed => max.saturating_sub(n),
+            _ => 0,
+        }
+    }
+
+    /// Tops the gear back up by up to `n` uses. Returns how many it took.
+    pub fn restock(&mut self, n: u32) -> u32 {
+        let added = self.uses_missing().min(n);
+        if let Usage::Limited(left) = self.uses_left { self.uses_left = Usage::Limited(left + added); }
+        added
+    }
+
+    /// Pulls up to `n` uses out of the gear for someone else's. Returns how many it gave up.
+    pub fn draw(&mut self, n: u32) -> u32 {
+        let Usage::Limited(left) = self.uses_left else { return 0; };
+        if self.destroyed { return 0; }
+        let taken = left.min(n);
+        self.uses_left = Usage::Limited(left - taken);
+        taken
+    }
+
+    /// Cutters and charges make short work of a cave-in.
+    pub fn is_digging_tool(&self) -> bool {
+        matches!(self.id, GearID::PlasmaCutter | GearID::DetonationRemote) && !self.destroyed
+    }
+
+    /// Sensors and tripwires set at a chamber's threshold give the squad warning of what's waiting.
+    pub fn warns_of_ambush(&self) -> bool {
+        matches!(self.id, GearID::SensorNode | GearID::EchoBeacon | GearID::LaserTripwire) && self.can_use().is_ok()
+    }
+
+    pub fn scan_range(&self) -> Option<usize> {
+        GEAR_SCAN_RANGE.iter().find(|(id, _)| *id == self.id).map(|(_, r)| *r)
+    }
+
+    /// What this gear puts on the field when used, if it's a deployable.
+    pub fn deployable(&self) -> Option<DeployableStats> {
+        let mut stats = *DEPLOYABLE_STATS.iter().find(|d| d.id == self.id)?;
+        if let Some(EquipmentFlaw::DestructibleByEnemies { hp }) = self.flaw {
+            stats.hp = hp;
+        }
+        Some(stats)
+    }
+
+    pub fn tick_cooldown(&mut self) {
+        self.cooldown = self.cooldown.saturating_sub(1);
+    }
+}
+
+//
+
+// ====================== DEPLOYABLES ===========================
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeployableKind { Turret, Drone, Sensor, Mine, Tripwire, Decoy, Trap }
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Trigger { StartOfRound, EnterTile, Passive }
+
+#[derive(Debug, Copy, Clone)]
+pub struct DeployableStats {
+    id: GearID,
+    pub kind: DeployableKind,
+    pub trigger: Trigger,
+    pub hp: u32,            // 0 = can't be targeted
+    pub count: u8,          // pieces placed per use
+    pub turns: Option<u8>,  // None = stays until destroyed or triggered
+    pub dmg: u32,
+    pub range: Distance,
+    pub effect: Option<Effect>,
+}
+
+static DEPLOYABLE_STATS: &[DeployableStats] = &[
+    DeployableStats {
+        id:         GearID::AutoTurret,
+        kind:       DeployableKind::Turret,
+        trigger:    Trigger::StartOfRound,
+        hp:         50,
+        count:      1,
+        turns:      None,
+        dmg:        20,
+        range:      Distance::Near,
+        effect:     None,
+    },
+    DeployableStats {
+        id:         GearID::StabilizerDrone,
+        kind:       DeployableKind::Drone,
+        trigger:    Trigger::StartOfRound,
+        hp:         20,
+        count:      1,
+        turns:      Some(3),
+        dmg:        0,
+        range:      Distance::Near,
+        effect:     Some(Effect::Regen { hp_per_turn: 8, turns: 2 }),
+    },
+    DeployableStats {
+        id:         GearID::SensorNode,
+        kind:       DeployableKind::Sensor,
+        trigger:    Trigger::StartOfRound,
+        hp:         10,
+        count:      1,
+        turns:      Some(3),
+        dmg:        0,
+        range:      Distance::Normal,
+        effect:     Some(Effect::RevealTraits),
+    },
+    DeployableStats {
+        id:         GearID::PortableMinefield,
+        kind:       DeployableKind::Mine,
+        trigger:    Trigger::EnterTile,
+        hp:         0,
+        count:      3,
+        turns:      None,
+        dmg:        15,
+        range:      Distance::Close,
+        effect:     None,
+    },
+    DeployableStats {
+        id:         GearID::LaserTripwire,
+        kind:       DeployableKind::Tripwire,
+        trigger:    Trigger::EnterTile,
+        hp:         0,
+        count:      1,
+        turns:      None,
+        dmg:        30,
+        range:      Distance::Close,
+        effect:     Some(Effect::Stun { turns: 1, area: None }),
+    },
+    DeployableStats {
+        id:         GearID::HoloDecoy,
+        kind:       DeployableKind::Decoy,
+        trigger:    Trigger::Passive,
+        hp:         20,
+        count:      1,
+        turns:      Some(2),
+        dmg:        0,
+        range:      Distance::Close,
+        effect:     None,
+    },
+    DeployableStats {
+        id:         GearID::TrapKit,
+        kind:       DeployableKind::Trap,
+        trigger:    Trigger::EnterTile,
+        hp:         0,
+        count:      1,
+        turns:      None,
+        dmg:        0,
+        range:      Distance::Close,
+        effect:     Some(Effect::Stun { turns: 2, area: None }),
+    },
+];
+
+/// A piece of gear sitting in a chamber, acting on its own.
+#[derive(Debug, Clone)]
+pub struct Deployable {
+    pub name: &'static str,
+    pub stats: DeployableStats,
+    pub owner: usize,
+    pub pos: Position,
+    hp: u32,
+    turns_left: Option<u8>,
+    spent: bool,
+}
+
+impl Deployable {
+    pub fn new(name: &'static str, stats: DeployableStats, owner: usize, pos: Position) -> Self {
+        Deployable { name, stats, owner, pos, hp: stats.hp, turns_left: stats.turns, spent: false }
+    }
+
+    pub fn kind(&self) -> DeployableKind { self.stats.kind }
+
+    pub fn hp(&self) -> u32 { self.hp }
+
+    pub fn is_active(&self) -> bool {
+        !self.spent && self.turns_left != Some(0) && (self.stats.hp == 0 || self.hp > 0)
+    }
+
+    /// Turrets, drones, sensors and decoys stand on their tile; mines and traps lie on it.
+    pub fn blocks_tile(&self) -> bool {
+        matches!(self.stats.kind, DeployableKind::Turret | DeployableKind::Drone | DeployableKind::Sensor | DeployableKind::Decoy)
+    }
+
+    pub fn is_targetable(&self) -> bool { self.is_active() && self.stats.hp > 0 }
+
+    /// Decoys exist to be shot at; turrets earn attention by shooting.
+    pub fn threat(&self) -> f32 {
+        match self.stats.kind {
+            DeployableKind::Decoy => 80.0,
+            DeployableKind::Turret => 20.0,
+            _ => 0.0,
+        }
+    }
+
+    pub fn triggers_on(&self, trigger: Trigger) -> bool { self.is_active() && self.stats.trigger == trigger }
+
+    /// Mines, tripwires and traps go off once.
+    pub fn spend(&mut self) { self.spent = true; }
+
+    pub fn tick(&mut self) {
+        if let Some(t) = self.turns_left.as_mut() { *t = t.saturating_sub(1); }
+    }
+
+    pub fn accuracy(&self) -> f32 { 0.9 }
+
+    pub fn agility(&self) -> f32 { 0.1 }
+
+    pub fn damage_profile(&self) -> DamageProfile {
+        DamageProfile::new(DamageType::Ballistic, self.stats.dmg, self.stats.dmg / 2, 0)
+    }
+
+    pub fn range_mods(&self, tiles: u32) -> RangeMods {
+        Joker::range_mods(self.stats.range, RangeProfile::Standard, tiles)
+    }
+
+    pub fn take_hit(&mut self, event: &DamageEvent) -> Mitigated {
+        let result = Joker::mitigate(&event.profile, self.hp, 0);
+        self.hp = self.hp.safe_sub(result.hp_loss);
+        result
+    }
 }
 
 pub struct Armory;
 
 impl Armory {
     fn get_gear_info(id: GearID) -> GearInfo {
-        GEAR_INFO.iter().find(|g| g.id == id).expect(&format!("Invalid Gear ID: {:?}", id)).clone()
+        *GEAR_INFO.iter().find(|g| g.id == id).unwrap_or_else(|| panic!("Invalid Gear ID: {:?}", id))
     }
     
     fn get_gear_stats(id: GearID) -> GearStats {
-        GEAR_STATS.iter().find(|g| g.id == id).expect(&format!("Invalid Gear ID: {:?}", id)).clone()
+        *GEAR_STATS.iter().find(|g| g.id == id).unwrap_or_else(|| panic!("Invalid Gear ID: {:?}", id))
     }
 
     fn get_weapon_stats(id: WeaponID) -> WeaponStats {
-        WEAPON_STATS.iter().find(|w|  w.id == id).expect(&format!("Invalid Weapon ID: {:?}", id)).clone()
+        *WEAPON_STATS.iter().find(|w|  w.id == id).unwrap_or_else(|| panic!("Invalid Weapon ID: {:?}", id))
     }
 
     fn get_weapon_info(id: WeaponID) -> WeaponInfo {
-        WEAPON_INFO.iter().find(|w|  w.id == id).expect(&format!("Invalid Weapon ID: {:?}", id)).clone()
+        *WEAPON_INFO.iter().find(|w|  w.id == id).unwrap_or_else(|| panic!("Invalid Weapon ID: {:?}", id))
     }
 
     fn allowed_for_class(id: EquipmentID, class: TrooperClass) -> bool {
@@ -1776,7 +2431,7 @@ impl Armory {
         let allowed_weapons = Self::fetch_allowed_weapons(class);
         let mut weapons = vec![];
 
-        for (_, w) in allowed_weapons.into_iter().enumerate() {
+        for w in allowed_weapons.into_iter() {
             let weapon = Weapon::new(w);
             weapons.push(weapon);
         }
@@ -1786,10 +2441,10 @@ impl Armory {
 
     pub fn log_class_weapons(class: TrooperClass) {
         let weapons: Vec<Weapon> = Self::load_weapons(class);
-        println!("");
+        println!();
         log!(info, format!("Trooper Class: {:?}", class), false);
-        println!("");
-        for (_, weapon) in weapons.into_iter().enumerate() {
+        println!();
+        for weapon in weapons.into_iter() {
             log!(info, format!("<<<<<<<<< {:?} >>>>>>>>>", weapon.id), false);
             log!(info, format!("ID: {:?}", weapon.id), false);
             log!(info, format!("Name: {:?}", weapon.info.name), false);
@@ -1814,7 +2469,7 @@ impl Armory {
         let allowed_gear = Self::fetch_allowed_gear(class);
         let mut gear = vec![];
 
-        for (_, g) in allowed_gear.into_iter().enumerate() {
+        for g in allowed_gear.into_iter() {
             let item = Gear::new(g);
             gear.push(item);
         }
@@ -1825,7 +2480,7 @@ impl Armory {
     pub fn log_class_gear(class: TrooperClass) {
         let gear: Vec<Gear> = Self::load_gear(class);
         log!(info, format!("Trooper Class: {:?}", class), true);
-        for (_, item) in gear.into_iter().enumerate() {
+        for item in gear.into_iter() {
             log!(info, format!("+++++++++++ {:?} +++++++++++", item.id), false);
             log!(info, format!("Name: {:?}", item.info.name), false);
             log!(info, format!("Type: {:?}", item.info.r#type), false);
@@ -1890,7 +2545,7 @@ impl Armory {
     }
 
     pub fn log_loadout(loadout: &Loadout) {
-        for (_, weapon) in loadout.weapons.iter().enumerate() {
+        for weapon in loadout.weapons.iter() {
             log!(info, format!("<<<<<<<<< {:?} >>>>>>>>>", weapon.id), false);
             log!(info, format!("ID: {:?}", weapon.id), false);
             log!(info, format!("Name: {:?}", weapon.info.name), false);
@@ -1902,7 +2557,7 @@ impl Armory {
             log!(info, format!("Flaw: {:?}", weapon.flaw), true);
         }
 
-        for (_, item) in loadout.gear.iter().enumerate() {
+        for item in loadout.gear.iter() {
             log!(info, format!("+++++++++++ {:?} +++++++++++", item.id), false);
             log!(info, format!("Name: {:?}", item.info.name), false);
             log!(info, format!("Type: {:?}", item.info.r#type), false);
@@ -1962,4 +2617,86 @@ impl Loadout {
     pub fn mut_equipped_gear(&mut self) -> &mut Gear {
         &mut self.gear[self.egear_idx]
     }
+
+    pub fn gear_count(&self) -> usize { self.gear.len() }
+
+    pub fn equipped_gear_slot(&self) -> usize { self.egear_idx }
+
+    pub fn use_equipped_gear(&mut self) -> Result<GearActivation, GearUseError> {
+        self.mut_equipped_gear().activate()
+    }
+
+    pub fn gear(&self) -> &[Gear] { &self.gear }
+
+    pub fn gear_mut(&mut self) -> &mut [Gear] { &mut self.gear }
+
+    pub fn weapons(&self) -> &[Weapon] { &self.weapons }
+
+    /// Marks the item as destroyed, returning the passive bonus its owner just lost.
+    pub fn destroy_gear(&mut self, slot: usize) -> Option<StatModifier> {
+        let item = self.gear.get_mut(slot).filter(|g| !g.destroyed)?;
+        let lost = item.passive_modifier();
+        item.destroyed = true;
+        Some(lost)
+    }
+
+    pub fn tick_gear(&mut self) {
+        for item in self.gear.iter_mut() {
+            item.tick_cooldown();
+        }
+    }
+}
+
+#[cfg(test)]
+impl Gear {
+    /// Fresh gear looked up by name, for tests in other modules.
+    pub(crate) fn named(name: &str) -> Self {
+        let id = ALL_GEAR_IDS.iter()
+            .copied()
+            .find(|id| Armory::get_gear_info(*id).name == name)
+            .expect("no gear by that name");
+        Gear::new(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limited_gear_spends_a_use_per_activation() {
+        let mut frag = Gear::new(GearID::FragGrenade);
+        for left in (0..6).rev() {
+            assert!(frag.activate().is_ok());
+            assert_eq!(frag.uses_left(), Some(left));
+        }
+        assert_eq!(frag.activate().unwrap_err(), GearUseError::Depleted);
+    }
+
+    #[test]
+    fn cooldown_blocks_reuse_until_it_ticks_down() {
+        let mut scanner = Gear::new(GearID::HiveScanner);
+        assert!(scanner.activate().is_ok());
+        // One extra turn, since the owner's next begin_turn ticks it straight back down
+        assert_eq!(scanner.can_use(), Err(GearUseError::OnCooldown(4)));
+        for _ in 0..3 { scanner.tick_cooldown(); }
+        assert_eq!(scanner.can_use(), Err(GearUseError::OnCooldown(1)));
+        scanner.tick_cooldown();
+        assert_eq!(scanner.can_use(), Ok(()));
+        assert_eq!(scanner.uses_left(), None);
+    }
+
+    #[test]
+    fn one_use_per_run_gear_stays_spent() {
+        let mut sensor = Gear::new(GearID::SensorNode);
+        assert!(sensor.activate().is_ok());
+        assert_eq!(sensor.activate().unwrap_err(), GearUseError::SpentForRun);
+        assert_eq!(sensor.uses_left(), Some(5));
+    }
+
+    #[test]
+    fn wearables_cannot_be_activated() {
+        let mut plating = Gear::new(GearID::ReinforcedPlating);
+        assert_eq!(plating.activate().unwrap_err(), GearUseError::Passive);
+    }
 }
@@ -1,21 +1,78 @@
 #![allow(dead_code, unused_variables)]
 // TODO: Implement the turn_handler
-// TODO: Implement effect application
 
-use rand::SeedableRng;
+use rand::{Rng, SeedableRng};
 use rand::rngs::SmallRng;
 
 use crate::log;
 use crate::debug::LOG;
-use crate::utils::{RngHub, SafeSub};
-use crate::hive::Cartographer;
-use crate::bugs::{Broodmother, Bug, };
-use crate::troopers::{Commander, Trooper};
-use crate::combat::{Joker, HitOutcome, HitInputs, DamageInputs, AttackContext};
+use crate::utils::RngHub;
+use crate::hive::{Cartographer, ChamberType, Cover, CoverKind, Hazard, NestKind, Occupant, Position, Reaction};
+use crate::bugs::{AttackStyle, Broodmother, Bug, Movement, SquadTactic};
+use crate::troopers::{Commander, Trooper, Vitals};
+use crate::lore::LoreMaster;
+use crate::combat::{Joker, HitOutcome, HitInputs, DamageInputs, DamageEvent, DamageProfile, DamageSource, AttackContext, RangeMods};
+use crate::armory::{Area, DamageType, Deployable, DeployableKind, DeployableStats, Effect, EquipmentFlaw, GearActivation, TargetType, Trigger};
+
+/// Fraction of max HP below which support gear (heals, regen) is considered worth spending.
+const WOUNDED_RATIO: f32 = 0.5;
+/// Target score knocked off a cloaked trooper; bugs only go for one when there's nobody else.
+const CLOAKED_PENALTY: f32 = 1000.0;
+/// Noise from anything that goes bang: grenades, mines, blasts.
+const EXPLOSION_NOISE: f32 = 6.0;
+const TURRET_NOISE: f32 = 3.0;
+/// Loudmouths chatter every round whether they shoot or not.
+const LOUDMOUTH_NOISE: f32 = 2.0;
+/// Fire doesn't blow nests apart, but it does cook them.
+const NEST_BURN_DMG: u32 = 10;
+const SCAN_DETECT_CHANCE: f32 = 0.75;
+/// Per digger, per turn of clearing rubble.
+const PIN_CHANCE: f32 = 0.1;
+const PIN_DMG: u32 = 15;
+const DIG_NOISE: f32 = 1.0;
+/// Odds a cleared cave-in comes down again as the squad falls back through it.
+const RECOLLAPSE_CHANCE: f32 = 0.35;
+/// Evasion multipliers for anyone standing in water.
+const WADING_EVASION: f32 = 0.6;
+const SUBMERGED_EVASION: f32 = 1.5;
+/// Share of an Energy hit that arcs to everyone else in the water.
+const CONDUCT_RATIO: f32 = 0.4;
+const FIRE_TURNS: u8 = 3;
+const ACID_TURNS: u8 = 4;
+const CAVE_IN_DMG: u32 = 15;
+/// Extra digging a blast-triggered cave-in leaves behind.
+const CAVE_IN_RUBBLE: u32 = 4;
+const REST_HEAL_RATIO: f32 = 0.4;
+const REST_RESUPPLY_USES: u32 = 2;
+/// Per turn spent resting, before the hive's alert level is added on top.
+const REST_FOUND_CHANCE: f32 = 0.08;
+const SCAVENGE_HEAL_RATIO: f32 = 0.25;
+const INFECTION_CHANCE: f32 = 0.35;
+/// Odds a Spitter's hit leaves a pool of acid under its target.
+const SPIT_ACID_CHANCE: f32 = 0.35;
+const SLAM_TILES: u32 = 1;
+/// What a Hivemind can do for its network each round.
+const LINK_HEAL: u32 = 12;
+const LINK_FOCUS_TILES: u32 = 2;
+const LINK_BUFF: Effect = Effect::BuffAccuracy { acc: 10, turns: 2 };
 
 enum Combatant<'a> {
     Trooper(&'a Trooper),
     Bug(&'a Bug),
+    Deployable(&'a Deployable),
+}
+
+/// Who a bug is going after this turn.
+#[derive(Copy, Clone, Debug)]
+enum BugTarget { Trooper(usize), Deployable(usize) }
+
+impl BugTarget {
+    fn occupant(&self) -> Occupant {
+        match self {
+            BugTarget::Trooper(ti) => Occupant::Trooper(*ti),
+            BugTarget::Deployable(di) => Occupant::Deployable(*di),
+        }
+    }
 }
 
 impl<'a> Combatant<'a> {
@@ -23,26 +80,58 @@ impl<'a> Combatant<'a> {
         match self {
             Combatant::Trooper(t) => t.accuracy(),
             Combatant::Bug(b)     => b.accuracy(),
+            Combatant::Deployable(d) => d.accuracy(),
         }
     }
     fn agility(&self) -> f32 {
         match self {
             Combatant::Trooper(t) => t.agility(),
             Combatant::Bug(b)     => b.agility(),
+            Combatant::Deployable(d) => d.agility(),
         }
     }
-    /// Returns (base_dmg, base_hp_dmg, base_ap_dmg)
-    fn damage_tuple(&self) -> (u32, u32, u32) {
+    /// Unscaled damage profile (type, hp/ap split, pierce)
+    fn damage_profile(&self) -> DamageProfile {
         match self {
-            Combatant::Trooper(t) => t.damage(),
-            Combatant::Bug(b)     => b.damage(),
+            Combatant::Trooper(t) => t.damage_profile(),
+            Combatant::Bug(b)     => b.damage_profile(),
+            Combatant::Deployable(d) => d.damage_profile(),
         }
     }
-    /// Optional: attacker-side dmg modifier (Troopers have class dmg_mod, bugs maybe 1.0)
+    fn damage_source(&self) -> DamageSource {
+        match self {
+            Combatant::Trooper(t) => t.damage_source(),
+            Combatant::Bug(b)     => b.damage_source(),
+            Combatant::Deployable(d) => DamageSource::Gear(d.name),
+        }
+    }
+    fn range_mods(&self, tiles: u32) -> RangeMods {
+        match self {
+            Combatant::Trooper(t) => t.range_mods(tiles),
+            Combatant::Bug(b)     => b.range_mods(tiles),
+            Combatant::Deployable(d) => d.range_mods(tiles),
+        }
+    }
+    fn burns_cover(&self) -> bool {
+        match self {
+            Combatant::Trooper(t) => t.burns_cover(),
+            Combatant::Bug(_b)    => false,
+            Combatant::Deployable(_) => false,
+        }
+    }
+    fn is_explosive(&self) -> bool {
+        match self {
+            Combatant::Trooper(t) => t.is_explosive(),
+            Combatant::Bug(b)     => b.is_explosive(),
+            Combatant::Deployable(_) => false,
+        }
+    }
+    /// Attacker-side dmg modifier (Troopers have class dmg_mod, bugs bake it into damage)
     fn dmg_mod(&self) -> f32 {
         match self {
-            Combatant::Trooper(t) => 1.0,
+            Combatant::Trooper(t) => t.dmg_mod(),
             Combatant::Bug(_b)    => 1.0,
+            Combatant::Deployable(_) => 1.0,
         }
     }
 }
@@ -159,14 +248,36 @@ pub struct WaveSummary {
 pub struct CampaignSummary {
     pub waves_cleared: usize,
     pub last_wave: WaveSummary,
+    pub extracted: bool,
+    pub alert: f32,
 }
 
 impl CampaignSummary {
     pub fn summary(&self) {
-        log!(info, format!("⚔️ Waves Cleared: {:?}", self.waves_cleared), true);
+        log!(info, format!("⚔️ Waves Cleared: {:?}", self.waves_cleared), false);
+        log!(info, format!("🚁 Extracted: {}, final hive alert: {:.0}", self.extracted, self.alert), true);
     }
 }
 
+/// What the squad does with a breather in a Clearing. There's only time for one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RestOption { PatchWounds, RepairArmor, Resupply, Reorganize }
+
+impl RestOption {
+    /// Turns spent out in the open doing it.
+    fn turns(&self) -> u32 {
+        match self {
+            RestOption::PatchWounds => 3,
+            RestOption::RepairArmor | RestOption::Resupply => 2,
+            RestOption::Reorganize => 1,
+        }
+    }
+}
+
+/// Who gets a free round before the fight starts properly.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Surprise { None, Bugs, Troopers }
+
 pub struct Overwatch {
     turn: usize,
     master_rng: SmallRng,
@@ -174,6 +285,7 @@ pub struct Overwatch {
     commander: Commander,
     broodmother: Broodmother,
     joker: Joker,
+    loremaster: LoreMaster,
 }
 
 impl Overwatch {
@@ -187,6 +299,7 @@ impl Overwatch {
         let commander = Commander::new(3);
         let broodmother = Broodmother::new();
         let joker = Joker::new();
+        let loremaster = LoreMaster::new();
 
         Overwatch {
             turn,
@@ -194,21 +307,23 @@ impl Overwatch {
             cartographer,
             commander,
             broodmother,
-            joker
+            joker,
+            loremaster,
         }
     }
 
-    fn build_hit_inputs(attacker: &Combatant, defender: &Combatant) -> HitInputs {
-        HitInputs::new(attacker.accuracy(), defender.agility(), None, None)
+    fn build_hit_inputs(attacker: &Combatant, defender: &Combatant, cover: Option<f32>, range: &RangeMods) -> HitInputs {
+        HitInputs::new(attacker.accuracy(), defender.agility(), cover, None, Some(range.accuracy))
     }
 
-    fn build_dmg_inputs(attacker: &Combatant) -> DamageInputs {
-        let (dmg, hp_dmg, ap_dmg) = attacker.damage_tuple();
-        DamageInputs::new(dmg, hp_dmg, ap_dmg, attacker.dmg_mod())
+    fn build_dmg_inputs(attacker: &Combatant, range: &RangeMods) -> DamageInputs {
+        DamageInputs::new(attacker.damage_profile(), attacker.dmg_mod(), range.damage)
     }
 
-    fn build_context(attacker: Combatant, defender: Combatant, advantage: i8, clamp_min_max: (f32, f32), pity_streak: u8) -> AttackContext {
-        AttackContext::new(Self::build_hit_inputs(&attacker, &defender), Self::build_dmg_inputs(&attacker), advantage, clamp_min_max, pity_streak)
+    #[allow(clippy::too_many_arguments)]
+    fn build_context(attacker: Combatant, defender: Combatant, tiles: u32, cover: Option<f32>, advantage: i8, clamp_min_max: (f32, f32), pity_streak: u8) -> AttackContext {
+        let range = attacker.range_mods(tiles);
+        AttackContext::new(Self::build_hit_inputs(&attacker, &defender, cover, &range), Self::build_dmg_inputs(&attacker, &range), advantage, clamp_min_max, pity_streak)
     }
 
     fn any_trooper_alive(&self) -> bool {
@@ -227,6 +342,944 @@ impl Overwatch {
         wave.iter().position(|b| b.is_alive())
     }
 
+    /// Scores every trooper (and decoy/turret) by the bug's tactic: threat against distance, plus a
+    /// pull toward the wounded. Confused bugs swing at random; with nobody standing, they go for the downed.
+    fn bug_target(&mut self, bi: usize, wave: &[Bug]) -> Option<BugTarget> {
+        let grid = self.cartographer.grid();
+        let me = Occupant::Bug(bi);
+        let mut candidates: Vec<(BugTarget, f32, u32, f32)> = self.commander.team.iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_up())
+            .map(|(ti, t)| (BugTarget::Trooper(ti), t.threat(), grid.tiles_between(me, Occupant::Trooper(ti)).unwrap_or(u32::MAX), t.hp_ratio()))
+            .collect();
+        candidates.extend(self.cartographer.current_chamber().deployed().iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_targetable() && d.threat() > 0.0)
+            .map(|(di, d)| (BugTarget::Deployable(di), d.threat(), grid.tiles_between(me, Occupant::Deployable(di)).unwrap_or(u32::MAX), 1.0)));
+
+        if candidates.is_empty() {
+            return self.commander.team.iter().position(|t| t.is_downed()).map(BugTarget::Trooper);
+        }
+        if wave[bi].is_confused() {
+            return Some(candidates[self.master_rng.random_range(0..candidates.len())].0);
+        }
+        // Linked bugs go where their Hivemind points them
+        if self.broodmother.link().is_linked(bi)
+            && let Some(ti) = self.broodmother.link().focus()
+            && !self.commander.team[ti].is_cloaked()
+            && candidates.iter().any(|c| matches!(c.0, BugTarget::Trooper(t) if t == ti)) {
+            return Some(BugTarget::Trooper(ti));
+        }
+
+        // On ties, whoever still has open tiles around them
+        let crowding = |occ: Occupant| grid.position_of(occ)
+            .map_or(0, |p| grid.area(p, 1).into_iter().filter(|a| !grid.is_free(*a)).count()) as f32;
+        let w = wave[bi].target_weights();
+        let cloaked = |target: &BugTarget| matches!(target, BugTarget::Trooper(ti) if self.commander.team[*ti].is_cloaked());
+        let score = |(target, threat, tiles, hp_ratio): &(BugTarget, f32, u32, f32)| {
+            threat * w.threat - (*tiles).min(50) as f32 * w.distance + (1.0 - hp_ratio) * w.wounded
+                - crowding(target.occupant()) * 0.1
+                - if cloaked(target) { CLOAKED_PENALTY } else { 0.0 }
+        };
+        candidates.iter()
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+            .map(|c| c.0)
+    }
+
+    // ---- Grid ----
+
+    fn tiles_between(&self, ti: usize, bi: usize) -> Option<u32> {
+        self.cartographer.grid().tiles_between(Occupant::Trooper(ti), Occupant::Bug(bi))
+    }
+
+    fn nearest_bug_idx(&self, ti: usize, wave: &[Bug]) -> Option<usize> {
+        wave.iter()
+            .enumerate()
+            .filter(|(_, b)| b.is_targetable())
+            .min_by_key(|(bi, _)| self.tiles_between(ti, *bi).unwrap_or(u32::MAX))
+            .map(|(bi, _)| bi)
+    }
+
+    /// Fresh grid for the wave: squad on the left edge, bugs on the right.
+    fn deploy(&mut self, wave: &[Bug]) {
+        let team_len = self.commander.team.len();
+        self.cartographer.current_chamber_mut().clear_deployed();
+        self.commander.reset_threat();
+        let grid = self.cartographer.grid_mut();
+        grid.clear();
+        grid.clear_hazards();
+        grid.deploy_squad(team_len);
+        grid.deploy_wave(wave.len());
+        self.clear_fallen(wave);
+    }
+
+    /// The dead stop taking up tiles; downed troopers stay where they fell. Dead bugs leave a corpse
+    /// behind that the next wave can hide behind.
+    fn clear_fallen(&mut self, wave: &[Bug]) {
+        let grid = self.cartographer.grid_mut();
+        for (ti, t) in self.commander.team.iter().enumerate().filter(|(_, t)| !t.is_alive()) {
+            grid.remove(Occupant::Trooper(ti));
+        }
+        for (bi, _) in wave.iter().enumerate().filter(|(_, b)| !b.is_alive()) {
+            let Some(pos) = grid.position_of(Occupant::Bug(bi)) else { continue; };
+            grid.remove(Occupant::Bug(bi));
+            if grid.cover_at(pos).is_none() { grid.add_cover(pos, Cover::new(CoverKind::Corpse)); }
+        }
+    }
+
+    fn cover_between(&self, attacker: Occupant, defender: Occupant) -> Option<(Position, Cover)> {
+        let grid = self.cartographer.grid();
+        grid.cover_between(grid.position_of(attacker)?, grid.position_of(defender)?)
+    }
+
+    /// Cover and water both change how hard the defender is to hit: waders are sitting ducks, while
+    /// anything that can submerge all but vanishes. WADING_EVASION stands in for the agility water
+    /// costs, so Trooper::agility and Bug::agility stay dry-land numbers.
+    fn evasion_mult(&self, cover: Option<(Position, Cover)>, defender: Occupant, submerges: bool) -> Option<f32> {
+        let cover = cover.map(|(_, c)| c.level.evasion_mult());
+        if !self.cartographer.grid().in_water(defender) { return cover; }
+        let water = if submerges { SUBMERGED_EVASION } else { WADING_EVASION };
+        Some(cover.unwrap_or(1.0) * water)
+    }
+
+    /// An Energy hit on someone in the water arcs through it to everyone else wading, friend or bug.
+    fn conduct(&mut self, struck: Occupant, event: &DamageEvent, wave: &mut [Bug]) {
+        if event.profile.damage_type != DamageType::Energy || !self.cartographer.grid().in_water(struck) { return; }
+        let mut profile = event.profile;
+        profile.hp = (profile.hp as f32 * CONDUCT_RATIO) as u32;
+        profile.ap = (profile.ap as f32 * CONDUCT_RATIO) as u32;
+        // Still the shooter's hit, so the hive remembers it like any other
+        let arc = DamageEvent::new(profile, event.source);
+
+        log!(info, "⚡ The charge arcs through the water!", false);
+        for occ in self.cartographer.grid().submerged().into_iter().filter(|o| *o != struck) {
+            match occ {
+                Occupant::Trooper(ti) if self.commander.team[ti].is_up() => {
+                    let taken = self.commander.apply_damage_to_trooper(ti, &arc);
+                    log!(info, format!("Trooper#{} is shocked (lost hp={}, ap={})", ti + 1, taken.hp_loss, taken.ap_loss), false);
+                },
+                Occupant::Bug(bi) if wave[bi].is_alive() => {
+                    let taken = self.broodmother.bug_attacked(&mut wave[bi], &arc);
+                    log!(info, format!("Bug#{} is shocked (lost hp={}, ap={})", bi + 1, taken.hp_loss, taken.ap_loss), false);
+                },
+                _ => {},
+            }
+        }
+        self.clear_fallen(wave);
+    }
+
+    /// After a hit lands, the chamber's terrain may catch fire, come down or pool acid around the target.
+    /// Bugs leave acid through their Spit attack instead, so their own hits never pool it here.
+    fn terrain_reaction(&mut self, dtype: DamageType, source: DamageSource, at: Position, wave: &mut [Bug]) {
+        let ctype = self.cartographer.current_chamber().chamber_type();
+        let Some(rule) = Cartographer::terrain_rule(ctype, dtype) else { return; };
+        if matches!((rule.reaction, source), (Reaction::AcidPool, DamageSource::Bug(_))) { return; }
+        if self.master_rng.random::<f32>() >= rule.chance { return; }
+
+        let grid = self.cartographer.grid_mut();
+        match rule.reaction {
+            Reaction::Ignite => {
+                for pos in grid.area(at, 1) {
+                    grid.set_hazard(pos, Hazard::Fire { turns: FIRE_TURNS });
+                }
+                log!(info, format!("🔥 Everything around ({}, {}) catches fire", at.x, at.y), false);
+            },
+            Reaction::AcidPool => {
+                grid.set_hazard(at, Hazard::AcidPool { turns: ACID_TURNS });
+                log!(info, format!("Acid pools at ({}, {})", at.x, at.y), false);
+            },
+            Reaction::CaveIn => {
+                log!(info, format!("🪨 The blast brings the ceiling down around ({}, {})", at.x, at.y), false);
+                let event = DamageEvent::new(DamageProfile::new(DamageType::Physical, CAVE_IN_DMG, CAVE_IN_DMG / 2, 0), DamageSource::Environment);
+                for pos in grid.area(at, 1) {
+                    match self.cartographer.grid().occupant_at(pos) {
+                        Some(Occupant::Trooper(ti)) if self.commander.team[ti].is_up() => {
+                            let taken = self.commander.apply_damage_to_trooper(ti, &event);
+                            log!(info, format!("Trooper#{} is caught in the rockfall (lost hp={}, ap={})", ti + 1, taken.hp_loss, taken.ap_loss), false);
+                        },
+                        Some(Occupant::Bug(bi)) if wave[bi].is_alive() => {
+                            self.broodmother.bug_attacked(&mut wave[bi], &event);
+                        },
+                        None if self.cartographer.grid().cover_at(pos).is_none() => {
+                            self.cartographer.grid_mut().add_cover(pos, Cover::new(CoverKind::Rubble));
+                        },
+                        _ => {},
+                    }
+                }
+                self.cartographer.current_chamber_mut().add_rubble(CAVE_IN_RUBBLE);
+                self.clear_fallen(wave);
+            },
+        }
+    }
+
+    /// Start of round: fire and acid hurt whoever is standing in them, then burn down.
+    fn hazard_phase(&mut self, wave: &mut [Bug]) {
+        for (occ, hazard) in self.cartographer.grid().occupied_hazards() {
+            let event = DamageEvent::new(hazard.damage_profile(), DamageSource::Environment);
+            match occ {
+                Occupant::Trooper(ti) if self.commander.team[ti].is_up() => {
+                    let taken = self.commander.apply_damage_to_trooper(ti, &event);
+                    log!(info, format!("Trooper#{} stands in {:?} (lost hp={}, ap={})", ti + 1, hazard, taken.hp_loss, taken.ap_loss), false);
+                    if !self.commander.team[ti].is_up() { log!(info, format!("Trooper#{} down!", ti + 1), false); }
+                },
+                Occupant::Bug(bi) if wave[bi].is_targetable() => {
+                    self.broodmother.bug_attacked(&mut wave[bi], &event);
+                    if !wave[bi].is_alive() { log!(info, format!("Bug#{} down!", bi + 1), false); }
+                },
+                _ => {},
+            }
+        }
+        self.cartographer.grid_mut().tick_hazards();
+        self.clear_fallen(wave);
+    }
+
+    /// Start of round: families that share the wave back each other up. Rot Bloom heals here; the
+    /// rest of the bonuses ride on the bugs until next round.
+    fn synergy_phase(&mut self, wave: &mut [Bug], announce: bool) {
+        let grid = self.cartographer.grid();
+        let active = self.broodmother.apply_synergies(wave, |a, b| grid.tiles_between(Occupant::Bug(a), Occupant::Bug(b)));
+        for synergy in active {
+            if announce { log!(info, format!("🧬 The {} in the wave share {}", synergy.family, synergy.name), false); }
+            self.loremaster.record_synergy(synergy);
+        }
+        for b in wave.iter_mut().filter(|b| b.is_alive() && b.synergy_regen() > 0) {
+            b.heal(b.synergy_regen());
+        }
+    }
+
+    /// Rewires the HiveLink. Anyone whose Hivemind has died since the last rewiring reels from the
+    /// severed link and loses a turn.
+    fn sever_links(&mut self, wave: &mut [Bug]) {
+        let grid = self.cartographer.grid();
+        let old = self.broodmother.relink(wave, |a, b| grid.tiles_between(Occupant::Bug(a), Occupant::Bug(b)));
+        for (hub, m) in old.severed(wave) {
+            wave[m].apply_effect(Effect::Stun { turns: 1, area: None });
+            log!(info, format!("🔗 Bug#{}'s link to Bug#{} is severed; it reels", m + 1, hub + 1), false);
+        }
+    }
+
+    /// Start of round: each Hivemind tends its network, mending its most wounded member, turning
+    /// everyone on a trooper that gets too close, or otherwise sharpening their aim.
+    fn hive_link_phase(&mut self, wave: &mut [Bug], announce: bool) {
+        self.sever_links(wave);
+        self.broodmother.link_mut().set_focus(None);
+        let link = self.broodmother.link().clone();
+        for hub in link.hubs() {
+            let members = link.members_of(hub);
+            if announce {
+                log!(info, format!("🔗 Bug#{} links {} bugs into the hivemind", hub + 1, members.len()), false);
+            }
+            if !wave[hub].can_act() { continue; }
+
+            let wounded = members.iter()
+                .copied()
+                .filter(|&m| wave[m].hp_ratio() < WOUNDED_RATIO)
+                .min_by(|&a, &b| wave[a].hp_ratio().total_cmp(&wave[b].hp_ratio()));
+            let intruder = self.commander.team.iter()
+                .enumerate()
+                .filter(|(_, t)| t.is_up() && !t.is_cloaked())
+                .filter_map(|(ti, _)| self.tiles_between(ti, hub).map(|d| (ti, d)))
+                .filter(|(_, d)| *d <= LINK_FOCUS_TILES)
+                .min_by_key(|(_, d)| *d)
+                .map(|(ti, _)| ti);
+
+            if let Some(m) = wounded {
+                let healed = wave[m].heal(LINK_HEAL);
+                log!(info, format!("🔗 Bug#{} mends Bug#{} through the link (+{healed} hp)", hub + 1, m + 1), false);
+            } else if let Some(ti) = intruder {
+                self.broodmother.link_mut().set_focus(Some(ti));
+                log!(info, format!("🔗 Bug#{} turns its network on Trooper#{}", hub + 1, ti + 1), false);
+            } else {
+                for &m in members.iter() { wave[m].apply_effect(LINK_BUFF); }
+                log!(info, format!("🔗 Bug#{} sharpens its network's aim", hub + 1), false);
+            }
+        }
+    }
+
+    /// Fire burns cover away outright; explosives chew through it with the blast.
+    fn wear_cover(&mut self, cover: Option<(Position, Cover)>, (burns, explosive): (bool, bool), outcome: HitOutcome, event: &DamageEvent) {
+        let Some((pos, c)) = cover else { return; };
+        if matches!(outcome, HitOutcome::Miss) && !burns { return; }
+        let grid = self.cartographer.grid_mut();
+        let gone = if burns {
+            grid.destroy_cover(pos).is_some()
+        } else if explosive {
+            grid.damage_cover(pos, event.profile.hp.max(1))
+        } else {
+            false
+        };
+        if gone {
+            log!(info, format!("{:?} cover at ({}, {}) is destroyed", c.kind, pos.x, pos.y), false);
+        }
+    }
+
+    /// Blasts and fire from gear clear cover across their whole area.
+    fn blast_cover(&mut self, center: Position, effect: Effect) {
+        let (dmg, burns) = match effect {
+            Effect::AoE { dmg, .. } => (dmg, false),
+            Effect::Burn { aoe: true, .. } => (0, true),
+            _ => return,
+        };
+        let radius = effect.area().map_or(0, |a| a.radius());
+        let grid = self.cartographer.grid_mut();
+        let area = grid.area(center, radius);
+        for &pos in area.iter() {
+            let gone = if burns { grid.destroy_cover(pos).is_some() } else { grid.damage_cover(pos, dmg) };
+            if gone {
+                log!(info, format!("Cover at ({}, {}) is blown apart", pos.x, pos.y), false);
+            }
+        }
+        let dmg = if burns { NEST_BURN_DMG } else { dmg };
+        for nest in self.cartographer.current_chamber_mut().nests_mut().iter_mut().filter(|n| area.contains(&n.pos)) {
+            if nest.take_damage(dmg) {
+                log!(info, format!("💥 {:?} at ({}, {}) is destroyed", nest.kind, nest.pos.x, nest.pos.y), false);
+            }
+        }
+    }
+
+    // ---- Spawning ----
+
+    /// Puts fresh bugs on the grid at the chamber's spawn points. Bugs with nowhere to stand don't arrive.
+    fn spawn_bugs(&mut self, new: Vec<Bug>, wave: &mut Vec<Bug>, b_pity: &mut Vec<u8>, dmg_factor: f32) -> usize {
+        let mut new = new;
+        self.broodmother.rebalance_wave(&mut new, dmg_factor);
+        let points = self.cartographer.current_chamber().spawn_points();
+        let mut arrived = 0;
+        for (i, bug) in new.into_iter().enumerate() {
+            let bi = wave.len();
+            let from = points[i % points.len()];
+            if self.cartographer.grid_mut().place_near(Occupant::Bug(bi), from).is_none() { break; }
+            wave.push(bug);
+            b_pity.push(0);
+            arrived += 1;
+        }
+        arrived
+    }
+
+    /// Start of round: nests on cadence and the alerted hive send reinforcements into the fight.
+    fn reinforce_phase(&mut self, round: usize, wave: &mut Vec<Bug>, b_pity: &mut Vec<u8>, dmg_factor: f32) {
+        if round <= 1 { return; }
+        let due = self.cartographer.current_chamber().spawns_due(round);
+        let new = self.broodmother.reinforcements(due, &mut self.master_rng);
+        if new.is_empty() { return; }
+        let arrived = self.spawn_bugs(new, wave, b_pity, dmg_factor);
+        if arrived > 0 {
+            log!(info, format!("🪲 Reinforcements! {arrived} bugs crawl out of the walls"), false);
+        }
+    }
+
+    /// A living nest in weapon range, weakest first. Troopers with a bug in their face deal with that instead.
+    fn nest_in_reach(&self, ti: usize, bi: usize) -> Option<usize> {
+        if self.tiles_between(ti, bi).is_some_and(|t| t <= 1) { return None; }
+        let pos = self.cartographer.grid().position_of(Occupant::Trooper(ti))?;
+        let trooper = &self.commander.team[ti];
+        self.cartographer.current_chamber().nests().iter()
+            .enumerate()
+            .filter(|(_, n)| n.is_alive() && trooper.in_range(pos.tiles_to(n.pos)))
+            .min_by_key(|(_, n)| n.hp())
+            .map(|(ni, _)| ni)
+    }
+
+    /// Nests don't dodge; the shot lands at full weapon damage. Egg clusters that survive may burst early.
+    fn trooper_hits_nest(&mut self, ti: usize, ni: usize, wave: &mut Vec<Bug>, b_pity: &mut Vec<u8>, dmg_factor: f32) {
+        let profile = self.commander.team[ti].damage_profile();
+        let noise = self.commander.team[ti].weapon_noise();
+        let hatch = self.cartographer.current_chamber().hatch_chance();
+        let nest = &mut self.cartographer.current_chamber_mut().nests_mut()[ni];
+        let destroyed = nest.take_damage(profile.hp + profile.ap);
+        let (kind, hp) = (nest.kind, nest.hp());
+        self.broodmother.alert_mut().make_noise(noise);
+
+        log!(info, format!("Trooper#{} -> {kind:?}: {} dmg ({hp} left)", ti + 1, profile.hp + profile.ap), false);
+        if destroyed {
+            log!(info, format!("💥 {kind:?} destroyed! It won't spawn any more bugs"), false);
+        } else if kind == NestKind::EggCluster && self.master_rng.random::<f32>() < hatch {
+            let arrived = self.spawn_bugs(self.broodmother.spawn_test_wave(2), wave, b_pity, dmg_factor);
+            log!(info, format!("🥚 The disturbed eggs burst open: {arrived} bugs hatch"), false);
+        }
+    }
+
+    /// Spends a move to close on the bug if it's out of weapon range. Returns whether it can now fire.
+    fn trooper_approach(&mut self, ti: usize, bi: usize) -> bool {
+        let Some(tiles) = self.tiles_between(ti, bi) else { return false; };
+        if self.commander.team[ti].in_range(tiles) { return true; }
+        if !self.commander.team[ti].spend_move() { return false; }
+
+        let steps = self.commander.team[ti].move_range();
+        let Some(target) = self.cartographer.grid().position_of(Occupant::Bug(bi)) else { return false; };
+        let (mut moved, mut spent) = (0, 0);
+        while spent < steps {
+            let Some(pos) = self.cartographer.grid_mut().step_toward(Occupant::Trooper(ti), target) else { break; };
+            moved += 1;
+            spent += self.cartographer.grid().move_cost(pos);
+            // Friendly fire: only mines care who steps on them
+            let mine = self.cartographer.current_chamber().trap_at(pos)
+                .filter(|&di| self.cartographer.current_chamber().deployed()[di].kind() == DeployableKind::Mine);
+            if let Some(di) = mine {
+                self.spring_trap(di, Occupant::Trooper(ti), &mut []);
+                if !self.commander.team[ti].is_up() { return false; }
+            }
+        }
+        log!(info, format!("Trooper#{} moves {moved} tiles toward Bug#{}", ti + 1, bi + 1), false);
+
+        self.tiles_between(ti, bi).is_some_and(|t| self.commander.team[ti].in_range(t))
+    }
+
+    /// Walks the bug in one tile at a time so anything lying in its path gets a chance to go off.
+    /// Jumpers in range clear the distance in one bound; Burrowers tunnel under traps and water and
+    /// only come up once they're in reach.
+    fn bug_approach(&mut self, bi: usize, target: BugTarget, wave: &mut [Bug]) -> bool {
+        let occ = target.occupant();
+        let grid = self.cartographer.grid();
+        let Some(tiles) = grid.tiles_between(Occupant::Bug(bi), occ) else { return false; };
+        if wave[bi].in_reach(tiles) {
+            Self::surface(bi, wave);
+            return true;
+        }
+
+        let Some(goal) = grid.position_of(occ) else { return false; };
+        match wave[bi].movement() {
+            Movement::Leap if tiles <= wave[bi].leap_range() => {
+                let grid = self.cartographer.grid_mut();
+                grid.remove(Occupant::Bug(bi));
+                if let Some(pos) = grid.place_near(Occupant::Bug(bi), goal) {
+                    log!(info, format!("Bug#{} leaps over the line to ({}, {})", bi + 1, pos.x, pos.y), false);
+                }
+            },
+            movement => {
+                if movement == Movement::Burrow && !wave[bi].is_burrowed() {
+                    wave[bi].burrow();
+                    log!(info, format!("Bug#{} burrows underground", bi + 1), false);
+                }
+                let moved = self.bug_walk(bi, goal, target, wave);
+                // A tunnel that goes nowhere, or one dug too long, forces the bug back up
+                if wave[bi].is_burrowed() && (moved == 0 || wave[bi].tunnel()) {
+                    Self::surface(bi, wave);
+                }
+            },
+        }
+
+        let reached = self.cartographer.grid().tiles_between(Occupant::Bug(bi), occ).is_some_and(|t| wave[bi].in_reach(t));
+        if reached { Self::surface(bi, wave); }
+        wave[bi].is_alive() && wave[bi].can_act() && reached
+    }
+
+    /// Walks the bug toward `goal` and returns how many tiles it covered.
+    fn bug_walk(&mut self, bi: usize, goal: Position, target: BugTarget, wave: &mut [Bug]) -> u32 {
+        let (mut moved, mut spent) = (0, 0);
+        while spent < wave[bi].move_range() {
+            let Some(pos) = self.cartographer.grid_mut().step_toward(Occupant::Bug(bi), goal) else { break; };
+            moved += 1;
+            // Noodles swim and burrowers tunnel under it; everything else wades
+            let free = wave[bi].can_submerge() || wave[bi].is_burrowed();
+            spent += if free { 1 } else { self.cartographer.grid().move_cost(pos) };
+            if wave[bi].is_burrowed() { continue; }
+            if let Some(di) = self.cartographer.current_chamber().trap_at(pos)
+                && self.spring_trap(di, Occupant::Bug(bi), wave) { break; }
+        }
+        if moved > 0 {
+            log!(debug, format!("Bug#{} moves {moved} tiles toward {:?}", bi + 1, target), false);
+        }
+        moved
+    }
+
+    fn surface(bi: usize, wave: &mut [Bug]) {
+        if !wave[bi].is_burrowed() { return; }
+        wave[bi].surface();
+        log!(info, format!("Bug#{} erupts from the ground!", bi + 1), false);
+    }
+
+    /// A wave with nothing left above ground can't hide forever: every burrower is forced up.
+    fn unearth(wave: &mut [Bug]) {
+        let mut alive = wave.iter().filter(|b| b.is_alive()).peekable();
+        if alive.peek().is_none() || !alive.all(|b| b.is_burrowed()) { return; }
+        log!(info, "The ground heaves - the hive drives its burrowers to the surface", false);
+        for bi in 0..wave.len() {
+            if wave[bi].is_alive() { Self::surface(bi, wave); }
+        }
+    }
+
+    /// An Exploder in reach blows itself apart, catching troopers, bugs and cover next to it.
+    fn detonate(&mut self, bi: usize, wave: &mut [Bug]) {
+        let Some(center) = self.cartographer.grid().position_of(Occupant::Bug(bi)) else { return; };
+        let profile = wave[bi].detonation();
+        let event = DamageEvent::new(profile, wave[bi].damage_source());
+        wave[bi].lose_hp(wave[bi].hp());
+        self.broodmother.alert_mut().make_noise(EXPLOSION_NOISE);
+        log!(info, format!("💥 Bug#{}{} detonates!", bi + 1, wave[bi].badge()), false);
+
+        let caught: Vec<Occupant> = self.cartographer.grid().area(center, 1).into_iter()
+            .filter_map(|p| self.cartographer.grid().occupant_at(p))
+            .collect();
+        for occ in caught {
+            match occ {
+                Occupant::Trooper(ti) if self.commander.team[ti].is_up() => {
+                    let taken = self.commander.apply_damage_to_trooper(ti, &event);
+                    log!(info, format!("Trooper#{} is caught in the blast (lost hp={}, ap={})", ti + 1, taken.hp_loss, taken.ap_loss), false);
+                    if !self.commander.team[ti].is_up() { log!(info, format!("Trooper#{} down!", ti + 1), false); }
+                },
+                Occupant::Bug(other) if wave[other].is_targetable() => {
+                    self.broodmother.bug_attacked(&mut wave[other], &event);
+                    if !wave[other].is_alive() { log!(info, format!("Bug#{} down!", other + 1), false); }
+                },
+                _ => {},
+            }
+        }
+        self.blast_cover(center, Effect::AoE { dmg: profile.hp, aoe: Area::Immediate });
+        self.terrain_reaction(DamageType::Explosive, event.source, center, wave);
+        self.clear_fallen(wave);
+    }
+
+    /// A Tank standing next to the intended target, and no farther from the shooter, takes the shot instead.
+    fn body_blocker(&self, ti: usize, bi: usize, wave: &[Bug]) -> Option<usize> {
+        let grid = self.cartographer.grid();
+        let shooter = grid.position_of(Occupant::Trooper(ti))?;
+        let target = grid.position_of(Occupant::Bug(bi))?;
+        wave.iter()
+            .enumerate()
+            .filter(|(oi, b)| *oi != bi && b.is_targetable() && b.body_blocks())
+            .filter_map(|(oi, _)| grid.position_of(Occupant::Bug(oi)).map(|p| (oi, p)))
+            .find(|(_, p)| p.tiles_to(target) <= 1 && p.tiles_to(shooter) <= target.tiles_to(shooter))
+            .map(|(oi, _)| oi)
+    }
+
+    // ---- Gear ----
+
+    fn wants_gear(&self, ti: usize, wave: &[Bug]) -> bool {
+        let team = &self.commander.team;
+        if let Some(d) = team[ti].equipped_gear().deployable() {
+            return match d.kind {
+                DeployableKind::Drone => team.iter().any(|t| t.is_downed() || (t.is_up() && t.hp_ratio() < WOUNDED_RATIO)),
+                _ => Self::any_bug_alive(wave),
+            };
+        }
+        let Some(effect) = team[ti].equipped_gear().effect() else { return false; };
+        match effect {
+            Effect::Custom(_) => false,
+            Effect::Revive { .. } => team.iter().any(|t| t.is_downed()),
+            Effect::Regen { .. } => team.iter().any(|t| t.is_downed() || (t.is_up() && t.hp_ratio() < WOUNDED_RATIO)),
+            Effect::Heal { .. } => team.iter().any(|t| t.is_up() && t.hp_ratio() < WOUNDED_RATIO),
+            Effect::BuffAP { turns: 0, .. } => team.iter().any(|t| t.is_up() && t.armor_damaged()),
+            Effect::CleanseDebuffs { .. } => team.iter().any(|t| t.is_up() && t.has_debuff()),
+            _ => Self::any_bug_alive(wave),
+        }
+    }
+
+    fn ally_target(&self, ti: usize, effect: Effect) -> Option<usize> {
+        let team = &self.commander.team;
+        match effect {
+            Effect::Revive { .. } => team.iter().position(|t| t.is_downed()),
+            Effect::Regen { .. } if team.iter().any(|t| t.is_downed()) => team.iter().position(|t| t.is_downed()),
+            Effect::CleanseDebuffs { .. } => team.iter().position(|t| t.is_up() && t.has_debuff()),
+            Effect::BuffAP { turns: 0, .. } => team.iter()
+                .enumerate()
+                .filter(|(_, t)| t.is_up() && t.armor_damaged())
+                .max_by_key(|(_, t)| t.max_ap() - t.ap())
+                .map(|(i, _)| i),
+            _ => team.iter()
+                .enumerate()
+                .filter(|(_, t)| t.is_up())
+                .min_by(|(_, a), (_, b)| a.hp_ratio().total_cmp(&b.hp_ratio()))
+                .map(|(i, _)| i)
+                .or(Some(ti)),
+        }
+    }
+
+    fn break_cloak(&mut self, ti: usize) {
+        if self.commander.team[ti].break_cloak() {
+            log!(info, format!("Trooper#{}'s cloak flickers out as they attack", ti + 1), false);
+        }
+    }
+
+    fn trooper_gear_phase(&mut self, ti: usize, wave: &mut [Bug]) {
+        if !self.wants_gear(ti, wave) {
+            self.commander.team[ti].cycle_gear();
+            return;
+        }
+        match self.commander.team[ti].use_gear() {
+            Ok(activation) => self.resolve_gear(ti, activation, wave),
+            Err(err) => {
+                log!(debug, format!("Trooper#{} can't use {}: {:?}", ti + 1, self.commander.team[ti].equipped_gear().name(), err), false);
+                self.commander.team[ti].cycle_gear();
+            },
+        }
+    }
+
+    fn resolve_gear(&mut self, ti: usize, activation: GearActivation, wave: &mut [Bug]) {
+        self.commander.team[ti].add_threat(activation.threat);
+        self.broodmother.alert_mut().make_noise(activation.threat / 5.0);
+        if matches!(activation.effect, Effect::AoE { .. }) {
+            self.broodmother.alert_mut().make_noise(EXPLOSION_NOISE);
+        }
+        if let Some(range) = activation.scan
+            && self.cartographer.scout_ahead(range, self.turn) > 0 {
+            log!(info, format!("📡 {} maps out the next {range} chamber(s)", activation.name), false);
+            self.cartographer.log_known_map();
+        }
+        if let Some(stats) = activation.deploy {
+            if matches!(stats.kind, DeployableKind::Turret | DeployableKind::Mine | DeployableKind::Tripwire | DeployableKind::Trap) {
+                self.broodmother.observe(SquadTactic::Traps);
+            }
+            self.place_deployable(ti, activation.name, stats, wave);
+            self.apply_gear_flaw(ti, activation.flaw);
+            return;
+        }
+
+        match activation.effect {
+            Effect::AoE { .. } => self.broodmother.observe(SquadTactic::Explosives),
+            Effect::Stun { .. } | Effect::Pacify { .. } | Effect::Confuse { .. } | Effect::Immobilize { .. } | Effect::Knockback { .. } => {
+                self.broodmother.observe(SquadTactic::CrowdControl);
+            },
+            _ => {},
+        }
+
+        if matches!(activation.target, TargetType::Enemy | TargetType::All)
+            || (matches!(activation.target, TargetType::Area) && !activation.effect.is_beneficial()) {
+            self.break_cloak(ti);
+        }
+
+        let user = &self.commander.team[ti];
+        let effect = activation.effect.scale_support(user.healing_output(), user.repair_output());
+        log!(info, format!("Trooper#{} uses {} -> {:?} ({:?})", ti + 1, activation.name, effect, activation.target), false);
+
+        match (activation.target, effect.is_beneficial()) {
+            (TargetType::Itself, _) => self.commander.team[ti].apply_effect(effect),
+            (TargetType::Ally, _) => match self.ally_target(ti, effect) {
+                Some(ai) if matches!(activation.flaw, Some(EquipmentFlaw::NoEffectIfTargetAtMax))
+                    && self.commander.team[ai].at_max_for(&effect) => {
+                    log!(info, format!("{} has no effect; Trooper#{} is already at max", activation.name, ai + 1), false);
+                },
+                Some(ai) => self.commander.team[ai].apply_effect(effect),
+                None => {},
+            },
+            (TargetType::Area, true) => {
+                for t in self.commander.team.iter_mut().filter(|t| t.is_up()) {
+                    t.apply_effect(effect);
+                }
+            },
+            (TargetType::Enemy, _) => {
+                if let Some(bi) = self.nearest_bug_idx(ti, wave) {
+                    if wave[bi].resists_gear(activation.flaw) {
+                        log!(info, format!("{} has no effect on elite Bug#{} {}", activation.name, bi + 1, wave[bi].badge()), false);
+                        self.apply_gear_flaw(ti, activation.flaw);
+                        return;
+                    }
+                    self.broodmother.bug_affected(&mut wave[bi], effect, DamageSource::Gear(activation.name));
+                    if let Effect::Knockback { dist } = effect
+                        && !wave[bi].rank().immune_to(&effect) {
+                        self.knockback(ti, bi, dist.max_tiles().min(3));
+                    }
+                }
+            },
+            (TargetType::Area, false) => {
+                let grid = self.cartographer.grid();
+                let center = self.nearest_bug_idx(ti, wave).and_then(|bi| grid.position_of(Occupant::Bug(bi)));
+                let hit: Vec<usize> = match (center, effect.area()) {
+                    (Some(c), Some(area)) => grid.area(c, area.radius()).into_iter()
+                        .filter_map(|p| match grid.occupant_at(p) { Some(Occupant::Bug(bi)) => Some(bi), _ => None })
+                        .collect(),
+                    _ => (0..wave.len()).collect(),
+                };
+                for bi in hit {
+                    if wave[bi].is_targetable() && !wave[bi].resists_gear(activation.flaw) {
+                        self.broodmother.bug_affected(&mut wave[bi], effect, DamageSource::Gear(activation.name));
+                    }
+                }
+                if let Some(c) = center {
+                    self.blast_cover(c, effect);
+                    let dtype = match effect {
+                        Effect::AoE { .. } => Some(DamageType::Explosive),
+                        Effect::Burn { .. } => Some(DamageType::Burn),
+                        _ => None,
+                    };
+                    if let Some(dtype) = dtype { self.terrain_reaction(dtype, DamageSource::Gear(activation.name), c, wave); }
+                }
+            },
+            (TargetType::All, _) => {
+                for t in self.commander.team.iter_mut().filter(|t| t.is_up()) {
+                    t.apply_effect(effect);
+                }
+                for b in wave.iter_mut().filter(|b| b.is_targetable() && !b.resists_gear(activation.flaw)) {
+                    self.broodmother.bug_affected(b, effect, DamageSource::Gear(activation.name));
+                }
+            },
+        }
+
+        self.apply_gear_flaw(ti, activation.flaw);
+    }
+
+    /// Chargers slam their target back a tile; Spitters may leave a pool of acid where it stands.
+    fn attack_follow_up(&mut self, bi: usize, ti: usize, outcome: HitOutcome, target_pos: Option<Position>, wave: &[Bug]) {
+        if matches!(outcome, HitOutcome::Miss) { return; }
+        match wave[bi].attack_style() {
+            AttackStyle::Slam if self.commander.team[ti].is_up() => {
+                let grid = self.cartographer.grid_mut();
+                let Some(from) = grid.position_of(Occupant::Bug(bi)) else { return; };
+                let pushed = grid.push_away(Occupant::Trooper(ti), from, SLAM_TILES);
+                if pushed > 0 { log!(info, format!("Trooper#{} is slammed back {pushed} tile(s)", ti + 1), false); }
+            },
+            AttackStyle::Spit if self.master_rng.random::<f32>() < SPIT_ACID_CHANCE => {
+                let Some(pos) = target_pos else { return; };
+                self.cartographer.grid_mut().set_hazard(pos, Hazard::AcidPool { turns: ACID_TURNS });
+                log!(info, format!("🧪 Bug#{}'s spit pools into acid under Trooper#{}", bi + 1, ti + 1), false);
+            },
+            _ => {},
+        }
+    }
+
+    /// Elites and mini-bosses sometimes follow a landed hit with their species' trademark move.
+    fn signature_move(&mut self, bi: usize, ti: usize, wave: &mut [Bug]) {
+        let Some(sig) = wave[bi].signature() else { return; };
+        if self.master_rng.random::<f32>() >= wave[bi].rank().signature_chance() { return; }
+        if sig.on_self {
+            wave[bi].apply_effect(sig.effect);
+            log!(info, format!("Bug#{}{} uses {} -> {:?}", bi + 1, wave[bi].badge(), sig.name, sig.effect), false);
+        } else if self.commander.team[ti].is_up() {
+            self.commander.team[ti].apply_effect(sig.effect);
+            log!(info, format!("Bug#{}{} uses {} on Trooper#{} -> {:?}", bi + 1, wave[bi].badge(), sig.name, ti + 1, sig.effect), false);
+        }
+    }
+
+    fn knockback(&mut self, ti: usize, bi: usize, tiles: u32) {
+        let grid = self.cartographer.grid_mut();
+        let Some(from) = grid.position_of(Occupant::Trooper(ti)) else { return; };
+        let pushed = grid.push_away(Occupant::Bug(bi), from, tiles);
+        log!(info, format!("Bug#{} is knocked back {pushed} tiles", bi + 1), false);
+    }
+
+    // ---- Deployables ----
+
+    /// Sets gear down within two tiles of its owner, leaning toward the nearest bug. Mines, tripwires
+    /// and traps never share a tile.
+    fn place_deployable(&mut self, ti: usize, name: &'static str, stats: DeployableStats, wave: &mut [Bug]) {
+        let grid = self.cartographer.grid();
+        let Some(origin) = grid.position_of(Occupant::Trooper(ti)) else { return; };
+        let facing = self.nearest_bug_idx(ti, wave).and_then(|bi| grid.position_of(Occupant::Bug(bi)));
+        let chamber = self.cartographer.current_chamber();
+
+        let mut spots: Vec<Position> = grid.area(origin, 2).into_iter()
+            .filter(|p| grid.is_free(*p) && chamber.trap_at(*p).is_none())
+            .collect();
+        spots.sort_by_key(|p| facing.map_or(0, |f| p.tiles_to(f)));
+
+        for pos in spots.into_iter().take(stats.count as usize) {
+            let di = self.cartographer.current_chamber_mut().deploy(Deployable::new(name, stats, ti, pos));
+            log!(info, format!("Trooper#{} deploys {} at ({}, {})", ti + 1, name, pos.x, pos.y), false);
+            if stats.kind == DeployableKind::Sensor { self.sensor_sweep(di, wave); }
+        }
+    }
+
+    /// Start-of-round upkeep: turrets shoot, drones patch, sensors sweep, then everything ages a turn.
+    fn deployable_phase(&mut self, wave: &mut [Bug], clamp: (f32, f32), scale: f32) {
+        for di in 0..self.cartographer.current_chamber().deployed().len() {
+            let d = &self.cartographer.current_chamber().deployed()[di];
+            if !d.is_active() { continue; }
+            if d.triggers_on(Trigger::StartOfRound) {
+                match d.kind() {
+                    DeployableKind::Turret => self.turret_fire(di, wave, clamp, scale),
+                    DeployableKind::Drone => self.drone_pulse(di),
+                    DeployableKind::Sensor => self.sensor_sweep(di, wave),
+                    _ => {},
+                }
+            }
+            let d = &mut self.cartographer.current_chamber_mut().deployed_mut()[di];
+            d.tick();
+            if !d.is_active() {
+                log!(info, format!("{} powers down", d.name), false);
+            }
+        }
+        self.cartographer.current_chamber_mut().clear_inactive();
+    }
+
+    fn deployable_tiles(&self, di: usize, occ: Occupant) -> Option<u32> {
+        self.cartographer.grid().tiles_between(Occupant::Deployable(di), occ)
+    }
+
+    fn turret_fire(&mut self, di: usize, wave: &mut [Bug], clamp: (f32, f32), scale: f32) {
+        let turret = &self.cartographer.current_chamber().deployed()[di];
+        let Some((bi, tiles)) = wave.iter()
+            .enumerate()
+            .filter(|(_, b)| b.is_targetable())
+            .filter_map(|(bi, _)| self.deployable_tiles(di, Occupant::Bug(bi)).map(|t| (bi, t)))
+            .filter(|(_, t)| turret.range_mods(*t).usable)
+            .min_by_key(|(_, t)| *t) else { return; };
+
+        let cover = self.cover_between(Occupant::Deployable(di), Occupant::Bug(bi));
+        let atk = Combatant::Deployable(turret);
+        let source = atk.damage_source();
+        let evasion = self.evasion_mult(cover, Occupant::Bug(bi), wave[bi].can_submerge());
+        let ctx = Self::build_context(atk, Combatant::Bug(&wave[bi]), tiles, evasion, 0, clamp, 0);
+        let res = Joker::resolve(&mut self.master_rng, &ctx, scale);
+        let event = DamageEvent::new(res.final_dmg, source);
+        let taken = self.broodmother.bug_attacked(&mut wave[bi], &event);
+        self.broodmother.alert_mut().make_noise(TURRET_NOISE);
+
+        log!(info, format!("{} -> Bug#{}: {:?} | lost hp={}, ap={}", turret.name, bi + 1, res.outcome, taken.hp_loss, taken.ap_loss), false);
+        if !wave[bi].is_alive() {
+            log!(info, format!("Bug#{} down!", bi + 1), false);
+            self.clear_fallen(wave);
+        }
+    }
+
+    /// Keeps a regen running on the worst-off trooper in range; downed troopers get stabilized by it.
+    fn drone_pulse(&mut self, di: usize) {
+        let drone = &self.cartographer.current_chamber().deployed()[di];
+        let Some(effect) = drone.stats.effect else { return; };
+        let reach = drone.stats.range.max_tiles();
+        let target = self.commander.team.iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_downed() || (t.is_up() && t.hp_ratio() < 1.0))
+            .filter(|(_, t)| !t.has_effect(|e| matches!(e, Effect::Regen { .. })))
+            .filter(|(ti, _)| self.deployable_tiles(di, Occupant::Trooper(*ti)).is_some_and(|t| t <= reach))
+            .min_by(|(_, a), (_, b)| a.hp_ratio().total_cmp(&b.hp_ratio()))
+            .map(|(ti, _)| ti);
+        if let Some(ti) = target {
+            log!(info, format!("{} tends to Trooper#{}", drone.name, ti + 1), false);
+            self.commander.team[ti].apply_effect(effect);
+        }
+    }
+
+    fn sensor_sweep(&mut self, di: usize, wave: &mut [Bug]) {
+        let sensor = &self.cartographer.current_chamber().deployed()[di];
+        let Some(effect) = sensor.stats.effect else { return; };
+        let reach = sensor.stats.range.max_tiles();
+        let mut found = 0;
+        for (bi, b) in wave.iter_mut().enumerate() {
+            if !b.is_alive() || b.is_scanned() { continue; }
+            if self.cartographer.grid().tiles_between(Occupant::Deployable(di), Occupant::Bug(bi)).is_some_and(|t| t <= reach) {
+                b.apply_effect(effect);
+                found += 1;
+            }
+        }
+        if found > 0 {
+            log!(info, format!("{} picks up {found} bugs", sensor.name), false);
+        }
+    }
+
+    /// Sets off whatever is lying on the tile. Returns whether the victim was stopped in its tracks.
+    fn spring_trap(&mut self, di: usize, victim: Occupant, wave: &mut [Bug]) -> bool {
+        let trap = &mut self.cartographer.current_chamber_mut().deployed_mut()[di];
+        trap.spend();
+        let (name, stats) = (trap.name, trap.stats);
+        let profile = match stats.kind {
+            DeployableKind::Mine => Some(DamageProfile::true_damage(DamageType::Explosive, stats.dmg)),
+            _ if stats.dmg > 0 => Some(DamageProfile::new(DamageType::Energy, stats.dmg, stats.dmg / 2, 0)),
+            _ => None,
+        };
+        let event = profile.map(|p| DamageEvent::new(p, DamageSource::Gear(name)));
+        if stats.kind == DeployableKind::Mine {
+            self.broodmother.alert_mut().make_noise(EXPLOSION_NOISE);
+        }
+
+        match victim {
+            Occupant::Bug(bi) => {
+                let taken = event.map(|e| self.broodmother.bug_attacked(&mut wave[bi], &e)).unwrap_or_default();
+                if let Some(effect) = stats.effect { self.broodmother.bug_affected(&mut wave[bi], effect, DamageSource::Gear(name)); }
+                log!(info, format!("Bug#{} sets off {} (lost hp={})", bi + 1, name, taken.hp_loss), false);
+                if !wave[bi].is_alive() {
+                    log!(info, format!("Bug#{} down!", bi + 1), false);
+                    self.clear_fallen(wave);
+                }
+                !wave[bi].is_alive() || !wave[bi].can_act()
+            },
+            Occupant::Trooper(ti) => {
+                let taken = event.map(|e| self.commander.apply_damage_to_trooper(ti, &e)).unwrap_or_default();
+                log!(info, format!("Trooper#{} steps on a {} (lost hp={})", ti + 1, name, taken.hp_loss), false);
+                !self.commander.team[ti].is_up()
+            },
+            Occupant::Deployable(_) => false,
+        }
+    }
+
+    /// Anything breakable right next to the bug.
+    fn adjacent_deployable(&self, bi: usize) -> Option<usize> {
+        self.cartographer.current_chamber().deployed().iter()
+            .enumerate()
+            .filter(|(_, d)| d.is_targetable())
+            .find(|(di, _)| self.deployable_tiles(*di, Occupant::Bug(bi)).is_some_and(|t| t <= 1))
+            .map(|(di, _)| di)
+    }
+
+    fn bug_hits_deployable(&mut self, bi: usize, di: usize, wave: &[Bug], clamp: (f32, f32), scale: f32) -> HitOutcome {
+        let tiles = self.deployable_tiles(di, Occupant::Bug(bi)).unwrap_or(1);
+        let target = &self.cartographer.current_chamber().deployed()[di];
+        let atk = Combatant::Bug(&wave[bi]);
+        let source = atk.damage_source();
+        let ctx = Self::build_context(atk, Combatant::Deployable(target), tiles, None, 0, clamp, 0);
+        let res = Joker::resolve(&mut self.master_rng, &ctx, scale);
+        let event = DamageEvent::new(res.final_dmg, source);
+
+        let target = &mut self.cartographer.current_chamber_mut().deployed_mut()[di];
+        let taken = target.take_hit(&event);
+        log!(info, format!("Bug#{} -> {}: {:?} | lost hp={} ({} left)", bi + 1, target.name, res.outcome, taken.hp_loss, target.hp()), false);
+        if !target.is_active() {
+            log!(info, format!("{} is destroyed!", target.name), false);
+            self.cartographer.current_chamber_mut().clear_inactive();
+        }
+        res.outcome
+    }
+
+    /// Spends the trooper's Action on a downed squadmate if one needs it. Medics revive, everyone else
+    /// can only stop the bleeding.
+    fn rescue_phase(&mut self, ti: usize) {
+        let medic = self.commander.team[ti].can_triage();
+        let Some(di) = self.commander.team.iter()
+            .position(|t| t.is_downed() && (medic || !t.is_stabilized())) else { return; };
+        if !self.commander.team[ti].spend_attack() { return; }
+
+        if medic {
+            let hp = self.commander.team[ti].triage_hp(&self.commander.team[di]);
+            self.commander.team[di].revive(hp);
+            log!(info, format!("Trooper#{} triages Trooper#{} back up ({hp} HP)", ti + 1, di + 1), false);
+        } else {
+            self.commander.team[di].stabilize();
+            log!(info, format!("Trooper#{} stabilizes Trooper#{}", ti + 1, di + 1), false);
+        }
+    }
+
+    /// Rolls for an ambush as the squad walks in. A Scout's BugScan or a sensor set at the threshold
+    /// turns it around on the bugs; fast reflexes at least keep anyone from being caught flat-footed.
+    fn ambush_check(&mut self, wave: &mut [Bug]) -> Surprise {
+        let chance = self.cartographer.current_chamber().ambush_chance();
+        if !self.broodmother.roll_ambush(chance, wave, &mut self.master_rng) { return Surprise::None; }
+
+        let team = &self.commander.team;
+        if let Some(si) = team.iter().position(|t| t.is_up() && t.can_scan())
+            && self.master_rng.random::<f32>() < SCAN_DETECT_CHANCE {
+            log!(info, format!("📡 Trooper#{} picks up the bugs lying in wait; the squad strikes first", si + 1), false);
+            return Surprise::Troopers;
+        }
+        let sentry = (0..team.len()).find_map(|ti| {
+            let t = &mut self.commander.team[ti];
+            if !t.is_up() || !t.can_act() { return None; }
+            t.use_warning_gear().map(|activation| (ti, activation))
+        });
+        if let Some((ti, activation)) = sentry {
+            log!(info, format!("Trooper#{} sets a {} at the threshold and it trips on the ambush", ti + 1, activation.name), false);
+            self.resolve_gear(ti, activation, wave);
+            return Surprise::Troopers;
+        }
+        if let Some(ti) = self.commander.team.iter().position(|t| t.is_up() && t.has_fast_reflexes()) {
+            log!(info, format!("⚠️ Ambush! Trooper#{} reacts in time and nobody is caught off guard", ti + 1), false);
+            return Surprise::None;
+        }
+        log!(info, "⚠️ Ambush! The bugs get a free round", false);
+        Surprise::Bugs
+    }
+
+    fn apply_gear_flaw(&mut self, ti: usize, flaw: Option<EquipmentFlaw>) {
+        match flaw {
+            Some(EquipmentFlaw::SelfDamage { dmg, chance }) if self.master_rng.random::<f32>() < chance => {
+                self.commander.team[ti].lose_hp(dmg);
+                log!(info, format!("Trooper#{} takes {dmg} self-damage", ti + 1), false);
+            },
+            Some(EquipmentFlaw::StunnedAfterUse { turns }) => {
+                self.commander.team[ti].apply_effect(Effect::Stun { turns, area: None });
+            },
+            _ => {},
+        }
+    }
+
     fn rebalance(&mut self, wave: &mut [Bug], hp_factor: f32, ap_factor: f32, dmg_factor: f32) {
         self.commander.rebalance_team(hp_factor, ap_factor);
         self.broodmother.rebalance_wave(wave, dmg_factor);
@@ -240,17 +1293,24 @@ impl Overwatch {
 
         // let wave = self.broodmother.spawn_test_wave(5);
         let count: usize = 5;
-        let waves: Vec<Vec<Bug>> = (0..count)
-            .map(|_| self.broodmother.spawn_test_wave(5))
+        let squad = self.commander.team.len();
+        let waves: Vec<Vec<Bug>> = (1..=count)
+            .map(|depth| {
+                self.broodmother.set_depth(depth, squad);
+                self.broodmother.spawn_wave()
+            })
             .collect();
 
         let campaign = self.run_waves(waves, SimOpts::default());
         campaign.summary();
+        self.broodmother.after_action_report();
+        self.loremaster.log_bestiary();
         // self.fight_sim(wave);
 
         self.log_all();
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn run_wave(&mut self, mut wave: Vec<Bug>, opts: SimOpts) -> WaveSummary {
         let clamp = opts.clamp;
         let scale = opts.scale;
@@ -258,7 +1318,12 @@ impl Overwatch {
         log!(debug, format!("❤️‍🔥 FIGHT START ❤️‍🔥"), true);
 
         self.rebalance(&mut wave, opts.rebalance_hp, opts.rebalance_ap, opts.rebalance_dmg);
+        self.broodmother.adapt_wave(&mut wave, &mut self.master_rng);
         self.broodmother.debug_wave(&wave);
+        self.deploy(&wave);
+        self.broodmother.reset_link();
+        self.sever_links(&mut wave);
+        let surprise = self.ambush_check(&mut wave);
 
         let mut round: usize = 1;
         let mut trooper_stats = RollStats::default();
@@ -273,20 +1338,93 @@ impl Overwatch {
         while self.any_trooper_alive() && Self::any_bug_alive(&wave) {
             log!(info, format!("----- Round {} -----", round), false);
 
+            self.reinforce_phase(round, &mut wave, &mut b_pity, opts.rebalance_dmg);
+            self.hazard_phase(&mut wave);
+            Self::unearth(&mut wave);
+            self.synergy_phase(&mut wave, round == 1);
+            self.hive_link_phase(&mut wave, round == 1);
+            if !self.any_trooper_alive() || !Self::any_bug_alive(&wave) { break; }
+
+            self.deployable_phase(&mut wave, clamp, scale);
+            if !Self::any_bug_alive(&wave) { break; }
+
             // --------------------
             // Trooper Phase
             // --------------------
-            for ti in 0..self.commander.team.len() {
+            let trooper_turns = if round == 1 && surprise == Surprise::Bugs { 0 } else { self.commander.team.len() };
+            for ti in 0..trooper_turns {
                 if !self.commander.team[ti].is_alive() { continue; }
-                let Some(bi) = Self::first_alive_bug_idx(&wave) else { break; };
+
+                let in_water = self.cartographer.grid().in_water(Occupant::Trooper(ti));
+                let trooper = &mut self.commander.team[ti];
+                let stunned = !trooper.can_act();
+                let was_downed = trooper.is_downed();
+                trooper.begin_turn();
+                trooper.tick_effects();
+                if trooper.is_downed() {
+                    // Going down to a damage tick costs the turn, not a bleed-out step as well
+                    if !was_downed {
+                        log!(info, format!("Trooper#{} down!", ti + 1), false);
+                        continue;
+                    }
+                    if in_water {
+                        trooper.drown();
+                        log!(info, format!("Trooper#{} is drowning!", ti + 1), false);
+                    } else {
+                        trooper.bleed_out();
+                    }
+                    match trooper.vitals() {
+                        Vitals::Downed { turns_left, stabilized: false } => {
+                            log!(info, format!("Trooper#{} is bleeding out ({turns_left} turns left)", ti + 1), false);
+                        },
+                        Vitals::Dead => {
+                            log!(info, format!("Trooper#{} has bled out", ti + 1), false);
+                        },
+                        _ => {},
+                    }
+                    continue;
+                }
+                if stunned {
+                    log!(info, format!("Trooper#{} is stunned", ti + 1), false);
+                    continue;
+                }
+
+                self.rescue_phase(ti);
+                self.trooper_gear_phase(ti, &mut wave);
+                self.clear_fallen(&wave);
+
+                let Some(bi) = self.nearest_bug_idx(ti, &wave) else { break; };
+                if let Some(ni) = self.nest_in_reach(ti, bi) {
+                    if self.commander.team[ti].spend_attack() {
+                        self.break_cloak(ti);
+                        self.trooper_hits_nest(ti, ni, &mut wave, &mut b_pity, opts.rebalance_dmg);
+                    }
+                    continue;
+                }
+                if !self.trooper_approach(ti, bi) { continue; }
+                if !self.commander.team[ti].spend_attack() { continue; }
+                self.break_cloak(ti);
+                let bi = match self.body_blocker(ti, bi, &wave) {
+                    Some(tank) => {
+                        log!(info, format!("Bug#{} body-blocks the shot meant for Bug#{}", tank + 1, bi + 1), false);
+                        tank
+                    },
+                    None => bi,
+                };
 
                 // Build once (immutable borrows), then apply damage (mutable) after
                 let pity = t_pity[ti];
-                let (outcome, final_dmg) = {
+                let tiles = self.tiles_between(ti, bi).unwrap_or(0);
+                let target_pos = self.cartographer.grid().position_of(Occupant::Bug(bi));
+                let cover = self.cover_between(Occupant::Trooper(ti), Occupant::Bug(bi));
+                let evasion = self.evasion_mult(cover, Occupant::Bug(bi), wave[bi].can_submerge());
+                let (outcome, event, breaks) = {
                     let atk = Combatant::Trooper(&self.commander.team[ti]);
                     let def = Combatant::Bug(&wave[bi]);
 
-                    let ctx = Self::build_context(atk, def, 0, clamp, pity);
+                    let source = atk.damage_source();
+                    let breaks = (atk.burns_cover(), atk.is_explosive());
+                    let ctx = Self::build_context(atk, def, tiles, evasion, 0, clamp, pity);
                     let res = Joker::resolve(&mut self.master_rng, &ctx, scale);
                     trooper_stats.record(res.outcome, res.hit_prob_used);
                     t_pity_stats.record(res.base_p, res.hit_prob_used, pity, res.outcome);
@@ -296,19 +1434,28 @@ impl Overwatch {
                         _ => t_pity[ti] = 0,
                     }
 
-                    (res.outcome, res.final_dmg)
+                    (res.outcome, DamageEvent::new(res.final_dmg, source), breaks)
                 };
 
-                let (dmg, hp_dmg, ap_dmg) = final_dmg;
-                self.broodmother.bug_attacked(&mut wave[bi], dmg, hp_dmg, ap_dmg);
+                let taken = self.broodmother.bug_attacked(&mut wave[bi], &event);
+                self.broodmother.observe(if tiles <= 1 { SquadTactic::CloseQuarters } else { SquadTactic::LongRange });
+                self.wear_cover(cover, breaks, outcome, &event);
+                self.commander.team[ti].add_threat((taken.hp_loss + taken.ap_loss) as f32 * 0.5);
+                self.broodmother.alert_mut().make_noise(self.commander.team[ti].weapon_noise());
+                if !matches!(outcome, HitOutcome::Miss) {
+                    self.conduct(Occupant::Bug(bi), &event, &mut wave);
+                    if let Some(pos) = target_pos { self.terrain_reaction(event.profile.damage_type, event.source, pos, &mut wave); }
+                }
 
                 log!(info, format!(
-                    "Trooper#{} -> Bug#{}: {:?}  | dmg={dmg}, hp={hp_dmg}, ap={ap_dmg}",
-                    ti + 1, bi + 1, outcome
+                    "Trooper#{} -> Bug#{}{}: {:?}  | {:?} hp={}, ap={}, pierce={}% -> lost hp={}, ap={}",
+                    ti + 1, bi + 1, wave[bi].badge(), outcome, event.profile.damage_type, event.profile.hp, event.profile.ap,
+                    event.profile.pierce, taken.hp_loss, taken.ap_loss
                 ), false);
 
                 if !wave[bi].is_alive() {
                     log!(info, format!("Bug#{} down!", bi + 1), false);
+                    self.clear_fallen(&wave);
                 }
                 if !Self::any_bug_alive(&wave) { break; }
             }
@@ -318,16 +1465,52 @@ impl Overwatch {
             // --------------------
             // Bug Phase
             // --------------------
-            for bi in 0..wave.len() {
+            self.sever_links(&mut wave);
+            let bug_turns = if round == 1 && surprise == Surprise::Troopers { 0 } else { wave.len() };
+            for bi in 0..bug_turns {
                 if !wave[bi].is_alive() { continue; }
-                let Some(ti) = self.first_alive_trooper_idx() else { break; };
+
+                let stunned = !wave[bi].can_act();
+                wave[bi].tick_effects();
+                if !wave[bi].is_alive() {
+                    log!(info, format!("Bug#{} down!", bi + 1), false);
+                    continue;
+                }
+                if stunned { continue; }
+
+                let Some(target) = self.bug_target(bi, &wave) else { continue; };
+                let target = if self.bug_approach(bi, target, &mut wave) {
+                    target
+                } else if let Some(di) = self.adjacent_deployable(bi).filter(|_| wave[bi].is_alive() && wave[bi].can_act()) {
+                    BugTarget::Deployable(di)
+                } else {
+                    continue;
+                };
+                let ti = match target {
+                    BugTarget::Trooper(ti) => ti,
+                    BugTarget::Deployable(di) => {
+                        self.bug_hits_deployable(bi, di, &wave, clamp, scale);
+                        continue;
+                    },
+                };
+                if wave[bi].attack_style() == AttackStyle::Detonate {
+                    self.detonate(bi, &mut wave);
+                    if !self.any_trooper_alive() { break; }
+                    continue;
+                }
 
                 let pity = b_pity[bi];
-                let (outcome, final_dmg) = {
+                let tiles = self.tiles_between(ti, bi).unwrap_or(0);
+                let target_pos = self.cartographer.grid().position_of(Occupant::Trooper(ti));
+                let cover = self.cover_between(Occupant::Bug(bi), Occupant::Trooper(ti));
+                let evasion = self.evasion_mult(cover, Occupant::Trooper(ti), false);
+                let (outcome, event, breaks) = {
                     let atk = Combatant::Bug(&wave[bi]);
                     let def = Combatant::Trooper(&self.commander.team[ti]);
 
-                    let ctx = Self::build_context(atk, def, 0, clamp, pity);
+                    let source = atk.damage_source();
+                    let breaks = (atk.burns_cover(), atk.is_explosive());
+                    let ctx = Self::build_context(atk, def, tiles, evasion, 0, clamp, pity);
                     let res = Joker::resolve(&mut self.master_rng, &ctx, scale);
                     bug_stats.record(res.outcome, res.hit_prob_used);
                     b_pity_stats.record(res.base_p, res.hit_prob_used, pity, res.outcome);
@@ -337,30 +1520,47 @@ impl Overwatch {
                         _ => b_pity[bi] = 0,
                     }
 
-                    (res.outcome, res.final_dmg)
+                    (res.outcome, DamageEvent::new(res.final_dmg, source), breaks)
                 };
 
-                let (dmg, hp_dmg, ap_dmg) = final_dmg;
-                self.commander.apply_damage_to_trooper(ti, dmg, hp_dmg, ap_dmg);
+                let taken = self.commander.apply_damage_to_trooper(ti, &event);
+                self.wear_cover(cover, breaks, outcome, &event);
+                if let Some(pos) = target_pos.filter(|_| !matches!(outcome, HitOutcome::Miss)) {
+                    self.terrain_reaction(event.profile.damage_type, event.source, pos, &mut wave);
+                }
 
                 log!(info, format!(
-                    "Bug#{} -> Trooper#{}: {:?}  | dmg={dmg}, hp={hp_dmg}, ap={ap_dmg}",
-                    bi + 1, ti + 1, outcome
+                    "Bug#{}{} -> Trooper#{}: {:?}  | {:?} hp={}, ap={} -> lost hp={}, ap={}",
+                    bi + 1, wave[bi].badge(), ti + 1, outcome, event.profile.damage_type, event.profile.hp, event.profile.ap,
+                    taken.hp_loss, taken.ap_loss
                 ), false);
 
-                if !self.commander.team[ti].is_alive() {
+                if matches!(outcome, HitOutcome::Hit | HitOutcome::Crit) {
+                    self.signature_move(bi, ti, &mut wave);
+                }
+                self.attack_follow_up(bi, ti, outcome, target_pos, &wave);
+
+                if !self.commander.team[ti].is_up() {
                     log!(info, format!("Trooper#{} down!", ti + 1), false);
+                    self.clear_fallen(&wave);
                 }
                 if !self.any_trooper_alive() { break; }
             }
 
+            self.settle_noise();
+
             round += 1;
+            self.turn += 1;
             if round > 50 {            // safety cap for runaway fights
                 log!(info, "Round cap reached; stopping.", false);
                 break;
             }
         }
         
+        for b in wave.iter().filter(|b| !b.is_alive() || b.is_scanned()) {
+            self.loremaster.record_bug(b);
+        }
+
         // Summary
         let alive_t = self.commander.team.iter().filter(|t| t.is_alive()).count();
         let alive_b = wave.iter().filter(|b| b.is_alive()).count();
@@ -382,27 +1582,213 @@ impl Overwatch {
         }
     }
 
-    pub fn between_waves(&mut self) {}
+    /// A Scout's BugScan sweeps the next chamber as the squad moves up.
+    fn bug_scan(&mut self) {
+        let Some(si) = self.commander.team.iter().position(|t| t.is_up() && t.can_scan()) else { return; };
+        if self.cartographer.scout_ahead(1, self.turn) > 0 {
+            log!(info, format!("📡 Trooper#{}'s BugScan sweeps the chamber ahead", si + 1), false);
+        }
+    }
+
+    /// End of round: loudmouths give the squad away, then the hive takes stock of the noise.
+    fn settle_noise(&mut self) {
+        let chatter = self.commander.team.iter().filter(|t| t.is_up() && t.is_loudmouth()).count();
+        let alert = self.broodmother.alert_mut();
+        alert.make_noise(chatter as f32 * LOUDMOUTH_NOISE);
+        if let Some(tier) = alert.settle_round() {
+            log!(info, format!("📢 Hive alert is now {:?} ({:.0})", tier, alert.level()), false);
+        }
+    }
+
+    /// Whichever need is most pressing across the squad; reorganizing is the fallback when nothing is.
+    fn pick_rest(&self) -> RestOption {
+        let up: Vec<&Trooper> = self.commander.team.iter().filter(|t| t.is_up()).collect();
+        let n = up.len().max(1) as f32;
+        let wounds = up.iter().map(|t| 1.0 - t.hp_ratio()).sum::<f32>() / n;
+        let armor = up.iter().map(|t| 1.0 - t.ap() as f32 / t.max_ap().max(1) as f32).sum::<f32>() / n;
+        // Charges only move between troopers, so what matters is how unevenly they've been spent
+        let spent = up.iter().map(|t| t.supplies_spent());
+        let supplies = spent.clone().fold(0.0, f32::max) - spent.fold(1.0, f32::min);
+        [
+            (RestOption::PatchWounds, wounds),
+            (RestOption::RepairArmor, armor * 0.8),
+            (RestOption::Resupply, supplies * 0.6),
+            (RestOption::Reorganize, 0.1),
+        ].into_iter()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(RestOption::Reorganize, |(opt, _)| opt)
+    }
+
+    fn apply_rest(&mut self, option: RestOption) {
+        if option == RestOption::Resupply {
+            self.commander.share_supplies(REST_RESUPPLY_USES);
+            return;
+        }
+        for (ti, t) in self.commander.team.iter_mut().enumerate().filter(|(_, t)| t.is_up()) {
+            match option {
+                RestOption::PatchWounds => {
+                    let hp = t.heal((t.max_hp() as f32 * REST_HEAL_RATIO) as u32);
+                    log!(info, format!("Trooper#{} patches up (+{hp} HP)", ti + 1), false);
+                },
+                RestOption::RepairArmor => {
+                    let ap = t.repair_armor(t.max_ap());
+                    log!(info, format!("Trooper#{} repairs their armor (+{ap} AP)", ti + 1), false);
+                },
+                RestOption::Resupply => {},
+                RestOption::Reorganize => t.reorganize(),
+            }
+        }
+    }
+
+    /// Whatever the chamber offers once it's quiet. Returns false if nobody survives it.
+    pub fn between_waves(&mut self, opts: SimOpts) -> bool {
+        match self.cartographer.current_chamber().chamber_type() {
+            ChamberType::Clearing => self.rest(opts),
+            ChamberType::FoodStorage => { self.scavenge(); true },
+            _ => true,
+        }
+    }
+
+    /// Desperate troopers pick through the larder. It patches them up, but some of it has turned.
+    fn scavenge(&mut self) {
+        for ti in 0..self.commander.team.len() {
+            let t = &mut self.commander.team[ti];
+            if !t.is_up() || t.hp_ratio() >= WOUNDED_RATIO { continue; }
+            let hp = t.heal((t.max_hp() as f32 * SCAVENGE_HEAL_RATIO) as u32);
+            log!(info, format!("🍖 Trooper#{} scavenges the larder (+{hp} HP)", ti + 1), false);
+            if self.master_rng.random::<f32>() < t.infection_risk(INFECTION_CHANCE) {
+                t.infect();
+                log!(info, format!("🤢 Trooper#{} has caught something", ti + 1), false);
+            }
+        }
+    }
+
+    /// Clearings give the squad a breather before pushing on. Every turn spent there is a chance for the
+    /// hive to find them; returns false if nobody survives the interruption.
+    fn rest(&mut self, opts: SimOpts) -> bool {
+        let option = self.pick_rest();
+        log!(info, format!("☀️ The squad catches its breath in the Clearing: {option:?}"), false);
+
+        for _ in 0..option.turns() {
+            self.settle_noise();
+            let found = REST_FOUND_CHANCE + self.broodmother.alert().level() / 400.0;
+            if self.master_rng.random::<f32>() < found {
+                log!(info, "The rest is cut short; bugs have found the squad!", false);
+                let wave = self.broodmother.spawn_test_wave(2 + self.broodmother.alert().wave_bonus());
+                let enc = self.run_wave(wave, opts);
+                self.commander.recover_downed();
+                return enc.trooper_alive > 0;
+            }
+        }
+        self.apply_rest(option);
+        true
+    }
+
+    /// Digs through the current chamber's rubble a turn at a time. Loose rock can pin a digger, who takes
+    /// a hit and sits out the next turn. Returns false if nobody is left standing to dig.
+    #[allow(clippy::needless_range_loop)]
+    fn clear_rubble(&mut self) -> bool {
+        log!(info, format!("🪨 The way on is choked with rubble ({} work to clear)", self.cartographer.current_chamber().rubble()), false);
+        let mut pinned = vec![false; self.commander.team.len()];
+        let mut turns = 0;
+        while self.cartographer.current_chamber().is_blocked() {
+            if !self.commander.team.iter().any(|t| t.is_up()) { return false; }
+            turns += 1;
+            let mut work = 0;
+            for ti in 0..self.commander.team.len() {
+                if !self.commander.team[ti].is_up() || std::mem::take(&mut pinned[ti]) { continue; }
+                work += self.commander.team[ti].dig_power();
+                self.broodmother.alert_mut().make_noise(DIG_NOISE);
+
+                if self.master_rng.random::<f32>() < PIN_CHANCE {
+                    let event = DamageEvent::new(DamageProfile::new(DamageType::Physical, PIN_DMG, PIN_DMG / 2, 0), DamageSource::Environment);
+                    let taken = self.commander.apply_damage_to_trooper(ti, &event);
+                    pinned[ti] = true;
+                    log!(info, format!("Trooper#{} is pinned by falling rock (lost hp={}, ap={})", ti + 1, taken.hp_loss, taken.ap_loss), false);
+                }
+            }
+            self.cartographer.current_chamber_mut().dig(work);
+            self.settle_noise();
+        }
+        log!(info, format!("The passage is clear after {turns} turns of digging"), false);
+        true
+    }
+
+    /// Walks the squad back out to the Entrance. The more alert the hive, the more chambers have
+    /// filled back up behind them. Returns whether anyone made it out.
+    fn extract(&mut self, opts: SimOpts) -> bool {
+        log!(info, "🚁 Extraction begins", true);
+        while let Some(chamber) = self.cartographer.retreat() {
+            log!(info, format!("Squad falls back through Chamber {} ({})", chamber.id(), chamber.describe()), false);
+            let depth = chamber.depth();
+            let caved = chamber.chamber_type() == ChamberType::Collapsed && self.master_rng.random::<f32>() < RECOLLAPSE_CHANCE;
+            let wave = if caved {
+                chamber.collapse();
+                log!(info, "🪨 The tunnel caves in again! The squad has to find another way round", false);
+                let side = self.cartographer.detour();
+                log!(info, format!("Squad cuts through a side passage (Chamber {})", side.id()), false);
+                self.broodmother.set_depth(side.depth(), self.commander.team.len());
+                // Nobody has cleared these passages; something always lives in them
+                Some(self.broodmother.spawn_test_wave(2 + self.broodmother.alert().wave_bonus()))
+            } else {
+                self.broodmother.set_depth(depth, self.commander.team.len());
+                self.broodmother.extraction_respawn(&mut self.master_rng)
+            };
+            self.cartographer.reveal_around(self.turn);
+            let Some(wave) = wave else { continue; };
+            log!(info, format!("{} bugs block the way out!", wave.len()), false);
+
+            let enc = self.run_wave(wave, opts);
+            self.cartographer.record_bugs(enc.bug_alive, self.turn);
+            if enc.trooper_alive == 0 { return false; }
+            self.commander.recover_downed();
+        }
+        self.any_trooper_alive()
+    }
 
-    pub fn run_waves(&mut self, mut waves: Vec<Vec<Bug>>, mut opts: SimOpts) -> CampaignSummary {
-        let mut cleared: usize = 0;
+    pub fn run_waves(&mut self, mut waves: Vec<Vec<Bug>>, opts: SimOpts) -> CampaignSummary {
+        let total = waves.len();
+        let counts: Vec<usize> = waves.iter().map(|w| w.len()).collect();
+        self.cartographer.populate(&counts);
 
         for (wi, mut wave) in waves.drain(..).enumerate() {
             log!(info, format!("🌊 Wave {} begin 🌊", wi + 1), true);
 
-            self.between_waves();
+            let chamber = self.cartographer.advance();
+            let (w, h) = chamber.grid().size();
+            let depth = chamber.depth();
+            log!(info, format!("Squad enters Chamber {} ({}, {w}x{h}, depth {depth})", chamber.id(), chamber.name()), false);
+            self.broodmother.set_depth(depth, self.commander.team.len());
+            self.cartographer.reveal_around(self.turn);
+            self.bug_scan();
+            self.cartographer.log_known_map();
+
+            self.broodmother.reinforce_for_alert(&mut wave);
+            let boss_chance = self.cartographer.current_chamber().miniboss_chance();
+            self.broodmother.crown_miniboss(&mut wave, boss_chance, &mut self.master_rng);
 
             let enc = self.run_wave(std::mem::take(&mut wave), opts);
+            self.cartographer.record_bugs(enc.bug_alive, self.turn);
 
             log!(info, format!("🌊 Wave {} end - Rounds: {}, Troopers Alive: {}, Bugs Alive: {}", wi + 1, enc.rounds, enc.trooper_alive, enc.bug_alive), true);
 
             if enc.trooper_alive == 0 {
-                return CampaignSummary { waves_cleared: cleared, last_wave: enc };
+                return CampaignSummary { waves_cleared: wi, last_wave: enc, extracted: false, alert: self.broodmother.alert().level() };
             }
 
-            cleared += 1;
+            self.commander.recover_downed();
+            self.commander.treat_sickness();
+
+            if self.cartographer.current_chamber().is_blocked() && !self.clear_rubble() {
+                return CampaignSummary { waves_cleared: wi + 1, last_wave: enc, extracted: false, alert: self.broodmother.alert().level() };
+            }
+            if !self.between_waves(opts) {
+                return CampaignSummary { waves_cleared: wi + 1, last_wave: enc, extracted: false, alert: self.broodmother.alert().level() };
+            }
         }
 
+        let extracted = self.extract(opts);
+
         let final_enc = WaveSummary {
             rounds: 0,
             trooper_alive: self.commander.team.iter().filter(|t| t.is_alive()).count(),
@@ -413,10 +1799,10 @@ impl Overwatch {
             bug_pity: PityStats::default(),
         };
 
-        CampaignSummary { waves_cleared: 0, last_wave: final_enc }
+        CampaignSummary { waves_cleared: total, last_wave: final_enc, extracted, alert: self.broodmother.alert().level() }
     }
 
-    pub fn fight_sim(&mut self, mut wave: Vec<Bug>) {
+    pub fn fight_sim(&mut self, wave: Vec<Bug>) {
         let _enc = self.run_wave(wave, SimOpts::default());
     }
 
@@ -424,3 +1810,128 @@ impl Overwatch {
         LOG.lock().unwrap().print_all();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bugs::BugClass;
+    use crate::hive::Grid;
+
+    /// An open field with Trooper#1 on the left edge and Bug#1 on the right.
+    fn field(width: u8, bug: Bug) -> (Overwatch, Vec<Bug>) {
+        let mut ovw = Overwatch::new();
+        ovw.cartographer.spawn_chambers(2);
+        let grid = ovw.cartographer.grid_mut();
+        *grid = Grid::new(width, 3);
+        grid.place(Occupant::Trooper(0), Position::new(0, 1));
+        grid.place(Occupant::Bug(0), Position::new(width - 1, 1));
+        (ovw, vec![bug])
+    }
+
+    fn wall_off(ovw: &mut Overwatch, x: u8) {
+        for y in 0..3 {
+            ovw.cartographer.grid_mut().add_cover(Position::new(x, y), Cover::new(CoverKind::Rubble));
+        }
+    }
+
+    #[test]
+    fn burrower_with_a_blocked_path_surfaces() {
+        let (mut ovw, mut wave) = field(7, Bug::of_class(BugClass::Burrower));
+        wall_off(&mut ovw, 3);
+        for _ in 0..4 {
+            assert!(!ovw.bug_approach(0, BugTarget::Trooper(0), &mut wave));
+        }
+        assert!(!wave[0].is_burrowed());
+        assert_eq!(ovw.cartographer.grid().position_of(Occupant::Bug(0)), Some(Position::new(4, 1)));
+    }
+
+    #[test]
+    fn burrower_surfaces_after_too_long_underground() {
+        let (mut ovw, mut wave) = field(40, Bug::of_class(BugClass::Burrower));
+        ovw.bug_approach(0, BugTarget::Trooper(0), &mut wave);
+        assert!(wave[0].is_burrowed());
+        ovw.bug_approach(0, BugTarget::Trooper(0), &mut wave);
+        ovw.bug_approach(0, BugTarget::Trooper(0), &mut wave);
+        assert!(!wave[0].is_burrowed());
+    }
+
+    #[test]
+    fn all_burrowed_wave_is_forced_up() {
+        let mut wave = vec![Bug::of_class(BugClass::Burrower), Bug::of_class(BugClass::Burrower)];
+        wave[0].burrow();
+        Overwatch::unearth(&mut wave);
+        assert!(wave[0].is_burrowed());
+
+        wave[1].burrow();
+        Overwatch::unearth(&mut wave);
+        assert!(wave.iter().all(|b| !b.is_burrowed() && b.is_alive()));
+    }
+
+    #[test]
+    fn jumper_leaps_over_a_wall() {
+        let (mut ovw, mut wave) = field(5, Bug::of_class(BugClass::Jumper));
+        wall_off(&mut ovw, 2);
+        ovw.bug_approach(0, BugTarget::Trooper(0), &mut wave);
+        let pos = ovw.cartographer.grid().position_of(Occupant::Bug(0)).unwrap();
+        assert_eq!(pos.tiles_to(Position::new(0, 1)), 1);
+    }
+
+    #[test]
+    fn bug_hits_leave_acid_to_the_spit_attack() {
+        let (mut ovw, mut wave) = field(5, Bug::of_class(BugClass::Spitter));
+        let at = Position::new(0, 1);
+        for _ in 0..100 {
+            ovw.terrain_reaction(DamageType::Corrosive, wave[0].damage_source(), at, &mut wave);
+        }
+        assert!(ovw.cartographer.grid().hazard_at(at).is_none());
+
+        for _ in 0..100 {
+            ovw.terrain_reaction(DamageType::Corrosive, DamageSource::Weapon("Acid Sprayer"), at, &mut wave);
+        }
+        assert!(matches!(ovw.cartographer.grid().hazard_at(at), Some(Hazard::AcidPool { .. })));
+    }
+
+    #[test]
+    fn bugs_look_past_a_cloaked_trooper() {
+        let (mut ovw, wave) = field(5, Bug::of_class(BugClass::Swarmer));
+        ovw.cartographer.grid_mut().place(Occupant::Trooper(1), Position::new(0, 0));
+        ovw.commander.team[0].apply_effect(Effect::Cloak { turns: 1 });
+        assert!(!matches!(ovw.bug_target(0, &wave), Some(BugTarget::Trooper(0))));
+
+        ovw.commander.team.truncate(1);
+        assert!(matches!(ovw.bug_target(0, &wave), Some(BugTarget::Trooper(0))));
+    }
+
+    /// A Hivemind on the right edge with one linked member beside it.
+    fn hive_field(width: u8) -> (Overwatch, Vec<Bug>) {
+        let (mut ovw, mut wave) = field(width, Bug::of_class(BugClass::Hivemind));
+        ovw.cartographer.grid_mut().place(Occupant::Bug(1), Position::new(width - 1, 0));
+        wave.push(Bug::of_class(BugClass::Swarmer).hivelinked());
+        (ovw, wave)
+    }
+
+    #[test]
+    fn hivemind_mends_its_wounded_member() {
+        let (mut ovw, mut wave) = hive_field(8);
+        let hp = wave[1].hp();
+        wave[1].lose_hp(hp - 1);
+        ovw.hive_link_phase(&mut wave, false);
+        assert_eq!(ovw.broodmother.link().members_of(0), vec![1]);
+        assert_eq!(wave[1].hp(), (1 + LINK_HEAL).min(hp));
+    }
+
+    #[test]
+    fn hivemind_turns_its_network_on_a_trooper_that_gets_close() {
+        let (mut ovw, mut wave) = hive_field(3);
+        ovw.hive_link_phase(&mut wave, false);
+        assert_eq!(ovw.broodmother.link().focus(), Some(0));
+    }
+
+    #[test]
+    fn hivemind_sharpens_its_network_when_nothing_else_needs_doing() {
+        let (mut ovw, mut wave) = hive_field(8);
+        ovw.hive_link_phase(&mut wave, false);
+        assert!(wave[1].has_effect(|e| matches!(e, Effect::BuffAccuracy { .. })));
+        assert_eq!(ovw.broodmother.link().focus(), None);
+    }
+}
@@ -1,24 +1,337 @@
 #![allow(dead_code)]
-// TODO: Add way to apply effects to Bug's stats'
 // TODO: Create way to take damage and way to attack
 // Imports
 
-use rand::{prelude::IndexedRandom};
+use rand::{Rng, prelude::IndexedRandom, seq::SliceRandom};
 use std::default::Default;
 
 use crate::{boost, log};
 use crate::utils::{SafeSub, RandBools as Bools};
 use crate::troopers::Trooper;
+use crate::hive::HiveAlert;
+use crate::armory::{ActiveEffect, DamageType, Distance, Effect, EquipmentFlaw};
+use crate::combat::{DamageEvent, DamageProfile, DamageSource, Joker, Mitigated, RangeMods, RangeProfile};
 
 // Enums, Traits, & Constants
 
+/// How a bug scores potential targets: threat against distance, with a pull toward the wounded.
 #[derive(Debug, Copy, Clone)]
-enum BugClass { Charger, Spitter, Swarmer, Hivemind, Pincer, Burrower, Exploder, Jumper, Tank }
-#[derive(Debug, Copy, Clone)]
+pub struct TargetWeights {
+    pub threat: f32,
+    pub distance: f32,
+    pub wounded: f32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum BugClass { Charger, Spitter, Swarmer, Hivemind, Pincer, Burrower, Exploder, Jumper, Tank }
+
+/// How a bug gets to its target.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Movement { Walk, Charge, Burrow, Leap }
+
+/// What a bug does once its target is in reach.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AttackStyle { Strike, Slam, Spit, Detonate }
+
+impl BugClass {
+    /// Chargers rush in, Burrowers tunnel under the fight, Jumpers leap over it; everything else walks.
+    pub fn movement(&self) -> Movement {
+        match self {
+            BugClass::Charger => Movement::Charge,
+            BugClass::Burrower => Movement::Burrow,
+            BugClass::Jumper => Movement::Leap,
+            _ => Movement::Walk,
+        }
+    }
+
+    /// Chargers knock their target back, Spitters leave acid behind, Exploders blow themselves up.
+    pub fn attack_style(&self) -> AttackStyle {
+        match self {
+            BugClass::Charger => AttackStyle::Slam,
+            BugClass::Spitter => AttackStyle::Spit,
+            BugClass::Exploder => AttackStyle::Detonate,
+            _ => AttackStyle::Strike,
+        }
+    }
+
+    /// Tanks step into shots meant for the bugs behind them.
+    pub fn body_blocks(&self) -> bool { matches!(self, BugClass::Tank) }
+}
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum BugTactic { Ambush, Rushdown, Flank, Protect, Bait, Adapt, Enrage, Distract, HiveLink }
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum BugSpecies { Snapper, Maw, Noodle, Priest, Skitter, Leaper, Sporebelly, Fleshcrawler, Blinker, Skulker, Tornaut, Queen }
 
+/// Where a bug stands in the hive's pecking order. Anything above Normal counts as elite for gear.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BugRank { #[default] Normal, Elite, MiniBoss }
+
+impl BugRank {
+    /// Stat multipliers (hp, ap, damage) and how many extra traits the rank guarantees.
+    fn mods(&self) -> (f32, f32, f32, usize) {
+        match self {
+            BugRank::Normal => (1.0, 1.0, 1.0, 0),
+            BugRank::Elite => (1.3, 1.2, 1.2, 1),
+            BugRank::MiniBoss => (2.0, 1.5, 1.4, 2),
+        }
+    }
+
+    /// Odds a landed hit also triggers the species' signature ability.
+    pub fn signature_chance(&self) -> f32 {
+        match self {
+            BugRank::Normal => 0.0,
+            BugRank::Elite => 0.3,
+            BugRank::MiniBoss => 0.5,
+        }
+    }
+
+    /// Mini-bosses can't be held down or pushed around by anything.
+    pub fn immune_to(&self, effect: &Effect) -> bool {
+        matches!(self, BugRank::MiniBoss) && matches!(effect,
+            Effect::Stun { .. } | Effect::Pacify { .. } | Effect::Confuse { .. }
+            | Effect::Immobilize { .. } | Effect::Knockback { .. })
+    }
+
+    /// Marker shown next to the bug in the log.
+    pub fn badge(&self) -> &'static str {
+        match self {
+            BugRank::Normal => "",
+            BugRank::Elite => "⭐",
+            BugRank::MiniBoss => "👑",
+        }
+    }
+}
+
+/// A species' trademark move, only used by elites and mini-bosses.
+#[derive(Debug, Copy, Clone)]
+pub struct Signature {
+    pub name: &'static str,
+    pub effect: Effect,
+    /// Lands on the bug itself rather than the trooper it hit.
+    pub on_self: bool,
+}
+
+static SIGNATURES: &[(BugSpecies, Signature)] = &[
+    (BugSpecies::Snapper, Signature { name: "Shell Crush", effect: Effect::Corrode { dmg: 5, turns: 2, stacks: 1 }, on_self: false }),
+    (BugSpecies::Maw, Signature { name: "Gnash", effect: Effect::Bleed { dmg: 6, turns: 3 }, on_self: false }),
+    (BugSpecies::Noodle, Signature { name: "Acid Spit", effect: Effect::Blind { turns: 2, acc_penalty: 20 }, on_self: false }),
+    (BugSpecies::Priest, Signature { name: "Hive Hymn", effect: Effect::Regen { hp_per_turn: 10, turns: 3 }, on_self: true }),
+    (BugSpecies::Skitter, Signature { name: "Frenzy", effect: Effect::BuffAccuracy { acc: 20, turns: 2 }, on_self: true }),
+    (BugSpecies::Leaper, Signature { name: "Pounce", effect: Effect::Stun { turns: 1, area: None }, on_self: false }),
+    (BugSpecies::Sporebelly, Signature { name: "Spore Burst", effect: Effect::Poison { dmg: 4, turns: 3, stacks: 2 }, on_self: false }),
+    (BugSpecies::Fleshcrawler, Signature { name: "Undermine", effect: Effect::BuffMove { mv: -1, turns: 2 }, on_self: false }),
+    (BugSpecies::Blinker, Signature { name: "Blindside", effect: Effect::Suppress { acc_penalty: 25, turns: 2 }, on_self: false }),
+    (BugSpecies::Skulker, Signature { name: "Rend", effect: Effect::Bleed { dmg: 8, turns: 2 }, on_self: false }),
+    (BugSpecies::Tornaut, Signature { name: "Quake", effect: Effect::Stun { turns: 1, area: None }, on_self: false }),
+    (BugSpecies::Queen, Signature { name: "Brood Call", effect: Effect::Regen { hp_per_turn: 12, turns: 3 }, on_self: true }),
+];
+
+/// What a family synergy does for the members it covers. Accuracy and damage are multipliers on top
+/// of 1.0, guard is the share of incoming damage shrugged off, regen is hp per round.
+#[derive(Default, Debug, Copy, Clone)]
+pub struct SynergyBonus {
+    accuracy: f32,
+    damage: f32,
+    guard: f32,
+    agility: f32,
+    regen: u32,
+}
+
+/// A family-wide bonus that kicks in when enough of its members share a wave.
+#[derive(Debug)]
+pub struct FamilySynergy {
+    pub family: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Living members needed in the wave.
+    min_members: usize,
+    /// Only members within this many tiles of the given species are covered.
+    anchor: Option<(BugSpecies, u32)>,
+    bonus: SynergyBonus,
+}
+
+pub static FAMILY_SYNERGIES: &[FamilySynergy] = &[
+    FamilySynergy {
+        family: "Tharnyx",
+        name: "Shell Wall",
+        description: "Two or more Tharnyx brace together, shrugging off 15% of incoming damage.",
+        min_members: 2,
+        anchor: None,
+        bonus: SynergyBonus { accuracy: 0.0, damage: 0.0, guard: 0.15, agility: 0.0, regen: 0 },
+    },
+    FamilySynergy {
+        family: "Varnith",
+        name: "Psychic Amplification",
+        description: "Varnith within 2 tiles of a Priest hit 20% harder.",
+        min_members: 2,
+        anchor: Some((BugSpecies::Priest, 2)),
+        bonus: SynergyBonus { accuracy: 0.0, damage: 0.2, guard: 0.0, agility: 0.0, regen: 0 },
+    },
+    FamilySynergy {
+        family: "Zunari",
+        name: "Swarm Sense",
+        description: "Three or more Zunari share what they see, gaining 15% accuracy.",
+        min_members: 3,
+        anchor: None,
+        bonus: SynergyBonus { accuracy: 0.15, damage: 0.0, guard: 0.0, agility: 0.0, regen: 0 },
+    },
+    FamilySynergy {
+        family: "Skolexid",
+        name: "Rot Bloom",
+        description: "Two or more Skolexid feed off each other's spores, regenerating 5 hp a round.",
+        min_members: 2,
+        anchor: None,
+        bonus: SynergyBonus { accuracy: 0.0, damage: 0.0, guard: 0.0, agility: 0.0, regen: 5 },
+    },
+    FamilySynergy {
+        family: "Xethari",
+        name: "Shadow Step",
+        description: "Two or more Xethari move in each other's blind spots, gaining 0.1 agility.",
+        min_members: 2,
+        anchor: None,
+        bonus: SynergyBonus { accuracy: 0.0, damage: 0.0, guard: 0.0, agility: 0.1, regen: 0 },
+    },
+];
+
+/// The wave's psychic network: every linkable bug answers to one Hivemind. Outcasts never join.
+#[derive(Default, Debug, Clone)]
+pub struct HiveLink {
+    /// (hub, member) pairs, by index into the wave.
+    links: Vec<(usize, usize)>,
+    /// Trooper the network is steering its members toward this round.
+    focus: Option<usize>,
+}
+
+impl HiveLink {
+    pub fn hubs(&self) -> Vec<usize> {
+        let mut hubs: Vec<usize> = self.links.iter().map(|(h, _)| *h).collect();
+        hubs.dedup();
+        hubs
+    }
+
+    pub fn members_of(&self, hub: usize) -> Vec<usize> {
+        self.links.iter().filter(|(h, _)| *h == hub).map(|(_, m)| *m).collect()
+    }
+
+    pub fn is_linked(&self, bi: usize) -> bool { self.links.iter().any(|(_, m)| *m == bi) }
+
+    pub fn focus(&self) -> Option<usize> { self.focus }
+
+    pub fn set_focus(&mut self, ti: Option<usize>) { self.focus = ti; }
+
+    /// (hub, member) pairs cut off by their hub dying in `wave`. Indices the wave doesn't have are
+    /// skipped, so a network left over from a bigger wave can't reach past the end.
+    pub fn severed(&self, wave: &[Bug]) -> Vec<(usize, usize)> {
+        self.links.iter()
+            .copied()
+            .filter(|&(h, m)| h < wave.len() && m < wave.len())
+            .filter(|&(h, m)| !wave[h].is_alive() && wave[m].is_alive())
+            .collect()
+    }
+}
+
+/// Broad strokes of how the squad fights, as the hive sees it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SquadTactic { CloseQuarters, LongRange, Explosives, CrowdControl, Traps }
+
+impl SquadTactic {
+    /// The tactic bugs switch to once the hive has seen enough of this one.
+    fn counter(&self) -> BugTactic {
+        match self {
+            SquadTactic::CloseQuarters => BugTactic::Distract,
+            SquadTactic::LongRange => BugTactic::Rushdown,
+            SquadTactic::Explosives => BugTactic::Flank,
+            SquadTactic::CrowdControl => BugTactic::Enrage,
+            SquadTactic::Traps => BugTactic::Ambush,
+        }
+    }
+}
+
+/// Share of a learned damage type that adapted bugs shrug off.
+const ADAPT_RESIST: f32 = 0.25;
+/// The hive needs this much to go on before it starts adapting.
+const ADAPT_MIN_DAMAGE: u32 = 200;
+const ADAPT_MIN_TACTICS: u32 = 8;
+
+/// What the hive has learned about how the squad fights, and what it has done about it.
+#[derive(Default, Debug, Clone)]
+pub struct HiveMemory {
+    damage: Vec<(DamageType, u32)>,
+    tactics: Vec<(SquadTactic, u32)>,
+    resisted: Vec<(DamageType, u32)>,
+    countered: Vec<(SquadTactic, u32)>,
+}
+
+impl HiveMemory {
+    fn bump<T: PartialEq>(tally: &mut Vec<(T, u32)>, key: T, amount: u32) {
+        match tally.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, n)) => *n += amount,
+            None => tally.push((key, amount)),
+        }
+    }
+
+    /// The most used entry and how hard the hive leans into countering it: nothing until one
+    /// clearly dominates, up to 60% once it's all the squad does.
+    fn favourite<T: Copy>(tally: &[(T, u32)], min_total: u32) -> Option<(T, f32)> {
+        let total: u32 = tally.iter().map(|(_, n)| n).sum();
+        if total < min_total { return None; }
+        let &(key, n) = tally.iter().max_by_key(|(_, n)| *n)?;
+        let strength = ((n as f32 / total as f32 - 0.4) * 1.5).clamp(0.0, 0.6);
+        (strength > 0.0).then_some((key, strength))
+    }
+
+    fn shares<T: std::fmt::Debug>(tally: &[(T, u32)]) -> String {
+        let total = tally.iter().map(|(_, n)| n).sum::<u32>().max(1) as f32;
+        let mut sorted: Vec<&(T, u32)> = tally.iter().collect();
+        sorted.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        sorted.iter().map(|(k, n)| format!("{k:?} {:.0}%", 100.0 * *n as f32 / total)).collect::<Vec<_>>().join(", ")
+    }
+}
+
+/// How deep a species has to be found before it joins the hive's waves.
+static SPECIES_DEPTH: &[(BugSpecies, usize)] = &[
+    (BugSpecies::Skitter, 0),
+    (BugSpecies::Snapper, 0),
+    (BugSpecies::Sporebelly, 0),
+    (BugSpecies::Noodle, 1),
+    (BugSpecies::Fleshcrawler, 1),
+    (BugSpecies::Skulker, 1),
+    (BugSpecies::Maw, 2),
+    (BugSpecies::Priest, 2),
+    (BugSpecies::Leaper, 2),
+    (BugSpecies::Blinker, 3),
+    (BugSpecies::Tornaut, 4),
+];
+
+/// What the hive throws at the squad at a given depth (chambers past the Entrance) and squad size.
+#[derive(Debug, Copy, Clone)]
+pub struct Difficulty {
+    pub depth: usize,
+    pub wave_size: usize,
+    pub trait_rolls: usize,
+    pub trait_chance: f32,
+    pub flaw_chance: f32,
+    pub elite_chance: f32,
+}
+
+impl Difficulty {
+    pub fn new(depth: usize, squad: usize) -> Self {
+        let squad = squad.clamp(1, 3);
+        Difficulty {
+            depth,
+            wave_size: squad + 1 + depth / 2,
+            trait_rolls: 2 + depth / 4,
+            trait_chance: (0.4 + 0.05 * depth as f32).min(0.8),
+            flaw_chance: (0.5 - 0.05 * depth as f32).max(0.1),
+            elite_chance: (0.04 * depth as f32).min(0.35),
+        }
+    }
+
+    fn species_pool(&self) -> Vec<BugSpecies> {
+        SPECIES_DEPTH.iter().filter(|(_, d)| *d <= self.depth).map(|(s, _)| *s).collect()
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone)]
 struct BugTraits {
     acidic: bool,
@@ -46,7 +359,9 @@ struct BugFlaws {
 #[derive(Default, Debug, Copy, Clone)]
 struct BugStats {
     hp: u32,
+    max_hp: u32,
     ap: u32,
+    max_ap: u32,
     damage: u32,
     accuracy: f32,
     agility: f32,
@@ -56,7 +371,9 @@ impl BugStats {
     fn new(hp: u32, ap: u32, damage: u32, accuracy: f32, agility: f32) -> Self {
         BugStats {
             hp,
+            max_hp: hp,
             ap,
+            max_ap: ap,
             damage,
             accuracy,
             agility
@@ -66,9 +383,13 @@ impl BugStats {
 
 // Bug Struct
 
+/// Rounds a burrower can tunnel before it has to surface.
+const MAX_BURROW_TURNS: u32 = 3;
+
 #[derive(Debug, Clone)]
 pub struct Bug {
     species: BugSpecies,
+    rank: BugRank,
     name: &'static str,
     family: &'static str,
     class: BugClass,
@@ -76,19 +397,30 @@ pub struct Bug {
     traits: BugTraits,
     flaws: BugFlaws,
     stats: BugStats,
+    effects: Vec<ActiveEffect>,
+    scanned: bool,
+    /// Underground and out of reach until it surfaces.
+    burrowed: bool,
+    /// Rounds spent underground since the last burrow.
+    burrow_turns: u32,
+    /// Whatever family synergy covers the bug this round.
+    synergy: SynergyBonus,
+    /// Damage type the hive has taught this bug to shrug off.
+    resists: Option<DamageType>,
 }
 
 impl Bug {
-    fn new(species: BugSpecies) -> Self {
+    fn new(species: BugSpecies, difficulty: &Difficulty) -> Self {
         let (class, name, family) = Self::get_species_info(species);
 
         let tactic = Self::determine_tactic(species);
-        let traits = Self::determine_traits(species);
-        let flaws = Self::determine_flaws(species);
+        let traits = Self::determine_traits(species, difficulty);
+        let flaws = Self::determine_flaws(species, difficulty);
         let stats = Self::get_stats(species, &traits, &flaws);
 
         Bug {
             species,
+            rank: BugRank::Normal,
             name,
             family,
             class,
@@ -96,6 +428,12 @@ impl Bug {
             traits,
             flaws,
             stats,
+            effects: Vec::new(),
+            scanned: false,
+            burrowed: false,
+            burrow_turns: 0,
+            synergy: SynergyBonus::default(),
+            resists: None,
         }
     }
 
@@ -119,9 +457,13 @@ impl Bug {
     }
 
     fn determine_tactic(species: BugSpecies) -> BugTactic {
+        *Self::tactic_options(species).choose(&mut rand::rng()).unwrap()
+    }
+
+    fn tactic_options(species: BugSpecies) -> Vec<BugTactic> {
         use BugSpecies::*;
         use BugTactic::*;
-        let options = match species {
+        match species {
             Snapper => vec![Rushdown, Enrage, Flank, Protect],
             Maw => vec![Flank, Ambush, Bait, Distract, HiveLink],
             Noodle => vec![Adapt, Flank, HiveLink, Distract],
@@ -134,8 +476,7 @@ impl Bug {
             Skulker => vec![Ambush, Flank, Bait],
             Tornaut => vec![Rushdown, Enrage, Protect, HiveLink, Distract],
             Queen => vec![Ambush, Rushdown, Flank, Protect, Bait, Adapt, Enrage, Distract, HiveLink],
-        };
-        *options.choose(&mut rand::rng()).unwrap()
+        }
     }
 
     fn get_species_trait(species: BugSpecies, traits: &mut BugTraits) {
@@ -237,7 +578,7 @@ impl Bug {
         }
     }
 
-    fn determine_traits(species: BugSpecies) -> BugTraits {
+    fn determine_traits(species: BugSpecies, difficulty: &Difficulty) -> BugTraits {
         let mut traits = BugTraits { ..Default::default() };
         let mut rng = rand::rng();
 
@@ -245,7 +586,7 @@ impl Bug {
 
         let mut trait_pool = Self::get_species_trait_pool(species, &mut traits);
 
-        Bools::roll_bools(&mut trait_pool, &mut rng, 2, 0.5, true);
+        Bools::roll_bools(&mut trait_pool, &mut rng, difficulty.trait_rolls, difficulty.trait_chance, true);
 
         traits
     }
@@ -327,12 +668,12 @@ impl Bug {
         }
     }
 
-    fn determine_flaws(species: BugSpecies) -> BugFlaws {
+    fn determine_flaws(species: BugSpecies, difficulty: &Difficulty) -> BugFlaws {
         let mut flaws = BugFlaws { ..Default::default() };
         let mut rng = rand::rng();
         let mut flaw_pool = Self::get_species_flaw_pool(species, &mut flaws);
 
-        Bools::maybe_roll_bools(&mut flaw_pool, &mut rng, 3, 0.5, false, 0.4);
+        Bools::maybe_roll_bools(&mut flaw_pool, &mut rng, 3, 0.5, false, difficulty.flaw_chance);
 
         flaws
     }
@@ -383,6 +724,8 @@ impl Bug {
 
         stats.hp = stats.hp.clamp(10, 300);
         stats.ap = stats.ap.clamp(0, 100);
+        stats.max_hp = stats.hp;
+        stats.max_ap = stats.ap;
         stats.damage = stats.damage.clamp(5, 100);
         stats.accuracy = stats.accuracy.clamp(0.1, 1.0);
         stats.agility = stats.agility.clamp(0.1, 1.0);
@@ -398,58 +741,437 @@ impl Bug {
         Self::apply_modifiers(&mut base, traits, flaws)
     }
 
+    /// Raises the bug to a higher rank: guaranteed extra traits from its pool, then scaled-up stats.
+    pub fn promote(&mut self, rank: BugRank) {
+        if rank <= self.rank { return; }
+        let extra = rank.mods().3 - self.rank.mods().3;
+        self.rank = rank;
+
+        let mut pool: Vec<&mut bool> = Self::get_species_trait_pool(self.species, &mut self.traits)
+            .into_iter()
+            .filter(|t| !**t)
+            .collect();
+        pool.shuffle(&mut rand::rng());
+        for t in pool.into_iter().take(extra) { *t = true; }
+
+        let (hp, ap, dmg, _) = rank.mods();
+        let mut stats = Self::get_stats(self.species, &self.traits, &self.flaws);
+        stats.hp = (stats.hp as f32 * hp).round() as u32;
+        stats.ap = (stats.ap as f32 * ap).round() as u32;
+        stats.damage = (stats.damage as f32 * dmg).round() as u32;
+        stats.max_hp = stats.hp;
+        stats.max_ap = stats.ap;
+        self.stats = stats;
+    }
+
+    pub fn rank(&self) -> BugRank { self.rank }
+
+    pub fn is_elite(&self) -> bool { self.rank > BugRank::Normal }
+
+    pub fn badge(&self) -> &'static str { self.rank.badge() }
+
+    /// The species' trademark move, if this bug ranks high enough to use it.
+    pub fn signature(&self) -> Option<Signature> {
+        if !self.is_elite() { return None; }
+        SIGNATURES.iter().find(|(s, _)| *s == self.species).map(|(_, sig)| *sig)
+    }
+
+    /// Whether gear carrying this flaw does nothing to the bug.
+    pub fn resists_gear(&self, flaw: Option<EquipmentFlaw>) -> bool {
+        matches!(flaw, Some(EquipmentFlaw::NoEffectOnElite)) && self.is_elite()
+    }
+
+    pub fn name(&self) -> &'static str { self.name }
+
+    pub fn family(&self) -> &'static str { self.family }
+
+    pub fn synergy_regen(&self) -> u32 { self.synergy.regen }
+
+    pub fn is_hivemind(&self) -> bool { self.class == BugClass::Hivemind }
+
+    /// Hivelinked bugs and HiveLink tacticians plug into a Hivemind, unless the hive has cast them out.
+    pub fn can_link(&self) -> bool {
+        (self.traits.hivelink || matches!(self.tactic, BugTactic::HiveLink)) && !self.flaws.outcast && !self.is_hivemind()
+    }
+
+    /// Adaptive bugs and Adapt tacticians pick up the hive's lessons twice as readily.
+    pub fn learns(&self) -> bool { self.traits.adaptive || matches!(self.tactic, BugTactic::Adapt) }
+
+    pub fn resists(&self) -> Option<DamageType> { self.resists }
+
+    /// Grows a resistance to `dtype`, turning adaptive if it wasn't already. False if nothing changed.
+    fn adapt(&mut self, dtype: DamageType) -> bool {
+        if self.resists == Some(dtype) { return false; }
+        if !self.traits.adaptive {
+            self.traits.adaptive = true;
+            self.stats.agility = (self.stats.agility + 0.1).min(1.0);
+        }
+        self.resists = Some(dtype);
+        true
+    }
+
+    /// Switches to `tactic` if the species knows it. False if it can't or already uses it.
+    fn counter(&mut self, tactic: BugTactic) -> bool {
+        if self.tactic == tactic || !Self::tactic_options(self.species).contains(&tactic) { return false; }
+        self.tactic = tactic;
+        true
+    }
+
+    pub fn hp_ratio(&self) -> f32 { self.stats.hp as f32 / self.stats.max_hp.max(1) as f32 }
+
     pub fn hp(&self) -> u32 { self.stats.hp }
 
     pub fn ap(&self) -> u32 { self.stats.ap }
 
-    pub fn damage(&self) -> (u32, u32, u32) {
-        let dmg = self.stats.damage;
-        let hp_dmg = (dmg as f32 * 0.4) as u32;
-        let ap_dmg = (dmg as f32 * 0.2) as u32;
+    pub fn max_hp(&self) -> u32 { self.stats.max_hp }
+
+    pub fn max_ap(&self) -> u32 { self.stats.max_ap }
+
+    /// Restores HP up to max, returning how much actually landed.
+    pub fn heal(&mut self, amount: u32) -> u32 {
+        if !self.is_alive() { return 0; }
+        let before = self.stats.hp;
+        self.stats.hp = (before + amount).min(self.stats.max_hp);
+        self.stats.hp - before
+    }
+
+    /// Bug attacks split raw damage 40/20 between flesh and armor; acidic bugs eat plating.
+    pub fn damage_profile(&self) -> DamageProfile {
+        let dmg = self.stats.damage as f32 * (1.0 + self.synergy.damage);
+        let damage_type = if self.traits.acidic { DamageType::Corrosive } else { DamageType::Physical };
+        DamageProfile::new(damage_type, (dmg * 0.4) as u32, (dmg * 0.2) as u32, 0)
+    }
+
+    pub fn damage_source(&self) -> DamageSource { DamageSource::Bug(self.name) }
+
+    pub fn accuracy(&self) -> f32 {
+        (self.stats.accuracy * (1.0 + ActiveEffect::accuracy_delta(&self.effects) + self.synergy.accuracy)).max(0.1)
+    }
+
+    pub fn agility(&self) -> f32 { (self.stats.agility + self.synergy.agility).min(1.0) }
+
+    pub fn is_explosive(&self) -> bool { self.traits.explosive }
+
+    /// Noodles slip under the surface in flooded chambers.
+    pub fn can_submerge(&self) -> bool { matches!(self.species, BugSpecies::Noodle) }
+
+    pub fn is_ambusher(&self) -> bool { matches!(self.tactic, BugTactic::Ambush) }
+
+    /// Rushers hit whatever is closest, enraged bugs chase the loudest trooper, ambushers and
+    /// flankers pick off the weak, distractors go after whoever the rest are ignoring.
+    pub fn target_weights(&self) -> TargetWeights {
+        use BugTactic::*;
+        let (threat, distance, wounded) = match self.tactic {
+            Rushdown => (0.2, 10.0, 0.0),
+            Enrage => (1.0, 2.0, 0.0),
+            Ambush | Flank => (0.3, 4.0, 40.0),
+            Protect => (1.0, 6.0, 0.0),
+            Bait => (0.5, 1.0, 20.0),
+            Distract => (-0.5, 3.0, 0.0),
+            Adapt | HiveLink => (0.6, 4.0, 10.0),
+        };
+        // Jumpers clear the front line, so distance barely matters to them
+        let distance = if self.movement() == Movement::Leap { distance * 0.25 } else { distance };
+        TargetWeights { threat, distance, wounded }
+    }
+
+    pub fn class(&self) -> BugClass { self.class }
+
+    pub fn movement(&self) -> Movement { self.class.movement() }
+
+    pub fn attack_style(&self) -> AttackStyle { self.class.attack_style() }
+
+    pub fn body_blocks(&self) -> bool { self.class.body_blocks() }
+
+    /// Chargers get two extra tiles when rushing in.
+    pub fn move_range(&self) -> u32 {
+        let charge = if self.movement() == Movement::Charge { 2 } else { 0 };
+        2 + (self.stats.agility * 3.0).round() as u32 + charge
+    }
+
+    /// How far a Jumper can clear in a single bound.
+    pub fn leap_range(&self) -> u32 { self.move_range() + 2 }
+
+    pub fn is_burrowed(&self) -> bool { self.burrowed }
+
+    pub fn burrow(&mut self) {
+        self.burrowed = true;
+        self.burrow_turns = 0;
+    }
+
+    pub fn surface(&mut self) {
+        self.burrowed = false;
+        self.burrow_turns = 0;
+    }
+
+    /// One more round underground; true once the bug has to come up for air.
+    pub fn tunnel(&mut self) -> bool {
+        self.burrow_turns += 1;
+        self.burrow_turns >= MAX_BURROW_TURNS
+    }
+
+    /// Alive and above ground.
+    pub fn is_targetable(&self) -> bool { self.is_alive() && !self.burrowed }
+
+    /// An Exploder's last act: explosive damage to everything next to it.
+    pub fn detonation(&self) -> DamageProfile {
+        let dmg = self.stats.damage as f32;
+        DamageProfile::new(DamageType::Explosive, (dmg * 0.8) as u32, (dmg * 0.4) as u32, 0)
+    }
+
+    /// Spitters attack from mid range; everything else has to close in.
+    pub fn reach(&self) -> Distance {
+        match self.class {
+            BugClass::Spitter => Distance::Normal,
+            _ => Distance::Close,
+        }
+    }
 
-        (dmg, hp_dmg, ap_dmg)
+    pub fn range_profile(&self) -> RangeProfile {
+        match self.reach() {
+            Distance::Close => RangeProfile::Melee,
+            _ => RangeProfile::Standard,
+        }
     }
 
-    pub fn accuracy(&self) -> f32 { self.stats.accuracy }
+    pub fn range_mods(&self, tiles: u32) -> RangeMods { Joker::range_mods(self.reach(), self.range_profile(), tiles) }
 
-    pub fn agility(&self) -> f32 { self.stats.agility }
+    pub fn in_reach(&self, tiles: u32) -> bool { self.range_mods(tiles).usable }
 
     pub fn is_alive(&self) -> bool { self.hp() > 0 }
 
-    pub fn attack(&self, target: &mut Trooper) {
-        let (dmg, hp_dmg, ap_dmg) = self.damage();
-        target.take_damage(dmg, hp_dmg, ap_dmg);
+    pub fn attack(&self, target: &mut Trooper) -> Mitigated {
+        target.take_hit(&DamageEvent::new(self.damage_profile(), self.damage_source()))
+    }
+
+    pub fn take_hit(&mut self, event: &DamageEvent) -> Mitigated {
+        let mut profile = event.profile;
+        let resist = if self.resists == Some(profile.damage_type) { ADAPT_RESIST } else { 0.0 };
+        let guard = (1.0 - self.synergy.guard) * (1.0 - resist);
+        profile.hp = (profile.hp as f32 * guard).round() as u32;
+        profile.ap = (profile.ap as f32 * guard).round() as u32;
+        let result = Joker::mitigate(&profile, self.stats.hp, self.stats.ap);
+        self.stats.ap = self.stats.ap.safe_sub(result.ap_loss);
+        self.stats.hp = self.stats.hp.safe_sub(result.hp_loss);
+        result
+    }
+
+    pub fn lose_hp(&mut self, dmg: u32) {
+        self.stats.hp = self.stats.hp.safe_sub(dmg);
     }
 
-    pub fn take_damage(&mut self, dmg: u32, hp_dmg: u32, ap_dmg: u32) {
+    /// Lands a lingering or utility effect. Upfront damage goes through Broodmother::bug_affected instead,
+    /// so the hive sees who dealt it.
+    pub fn apply_effect(&mut self, effect: Effect) {
+        if self.rank.immune_to(&effect) { return; }
+        match effect {
+            Effect::RevealTraits | Effect::MarkTarget => self.scanned = true,
+            _ => {},
+        }
+        if let Some(active) = ActiveEffect::new(effect) {
+            self.effects.push(active);
+        }
+    }
+
+    pub fn tick_effects(&mut self) {
+        if !self.is_alive() { return; }
+        let tick = ActiveEffect::tick_all(&mut self.effects);
+        self.heal(tick.heal);
         let stats = &mut self.stats;
-        boost!(stats, stats.ap != 0, ap -= ap_dmg);
-        boost!(stats, stats.ap == 0, hp -= hp_dmg);
-        boost!(stats, true, hp -= dmg);
-        boost!(stats, true, ap -= dmg);
+        boost!(stats, tick.hp_dmg > 0, hp -= tick.hp_dmg);
+        boost!(stats, tick.ap_dmg > 0, ap -= tick.ap_dmg);
+    }
+
+    pub fn has_effect(&self, pred: impl Fn(&Effect) -> bool) -> bool {
+        self.effects.iter().any(|e| pred(&e.effect))
     }
+
+    /// Stunned and pacified bugs lose their attack for the turn.
+    pub fn can_act(&self) -> bool {
+        self.is_alive() && !self.has_effect(|e| matches!(e, Effect::Stun { .. } | Effect::Pacify { .. }))
+    }
+
+    pub fn is_confused(&self) -> bool {
+        self.has_effect(|e| matches!(e, Effect::Confuse { .. }))
+    }
+
+    pub fn is_scanned(&self) -> bool { self.scanned }
 }
 
-pub struct Broodmother;
+pub struct Broodmother {
+    alert: HiveAlert,
+    difficulty: Difficulty,
+    link: HiveLink,
+    memory: HiveMemory,
+}
 
 impl Broodmother {
     pub fn new() -> Self {
-        Broodmother
+        Broodmother { alert: HiveAlert::new(), difficulty: Difficulty::new(1, 3), link: HiveLink::default(), memory: HiveMemory::default() }
+    }
+
+    pub fn memory(&self) -> &HiveMemory { &self.memory }
+
+    /// The hive takes note of how the squad just fought.
+    pub fn observe(&mut self, tactic: SquadTactic) {
+        HiveMemory::bump(&mut self.memory.tactics, tactic, 1);
+    }
+
+    /// Before a fight, the wave takes in what the hive has learned: some bugs grow resistant to the
+    /// squad's favourite damage type, others switch to a tactic that counters its favourite approach.
+    pub fn adapt_wave(&mut self, wave: &mut [Bug], rng: &mut impl Rng) {
+        let dtype = HiveMemory::favourite(&self.memory.damage, ADAPT_MIN_DAMAGE);
+        let tactic = HiveMemory::favourite(&self.memory.tactics, ADAPT_MIN_TACTICS);
+        let (mut resisted, mut countered) = (0, 0);
+        for bug in wave.iter_mut().filter(|b| b.is_alive()) {
+            let learns = bug.learns();
+            let odds = |p: f32| if learns { (p * 2.0).min(1.0) } else { p };
+            if let Some((d, p)) = dtype
+                && rng.random::<f32>() < odds(p) && bug.adapt(d) { resisted += 1; }
+            if let Some((t, p)) = tactic
+                && rng.random::<f32>() < odds(p) && bug.counter(t.counter()) { countered += 1; }
+        }
+        if let Some((d, _)) = dtype.filter(|_| resisted > 0) {
+            HiveMemory::bump(&mut self.memory.resisted, d, resisted);
+            log!(info, format!("🧠 The hive has learned: {resisted} bugs have grown resistant to {d:?}"), false);
+        }
+        if let Some((t, _)) = tactic.filter(|_| countered > 0) {
+            HiveMemory::bump(&mut self.memory.countered, t, countered);
+            log!(info, format!("🧠 The hive has learned: {countered} bugs switch to {:?} against the squad's {t:?}", t.counter()), false);
+        }
+    }
+
+    /// How the squad fought this run and how the hive answered it.
+    pub fn after_action_report(&self) {
+        let memory = &self.memory;
+        log!(info, "📋 ===== AFTER-ACTION REPORT =====", false);
+        log!(info, format!("Damage dealt: {}", HiveMemory::shares(&memory.damage)), false);
+        log!(info, format!("Squad tactics: {}", HiveMemory::shares(&memory.tactics)), false);
+        for (d, n) in memory.resisted.iter() {
+            log!(info, format!("🧠 {n} bugs grew resistant to {d:?}"), false);
+        }
+        for (t, n) in memory.countered.iter() {
+            log!(info, format!("🧠 {n} bugs turned to {:?} to counter {t:?}", t.counter()), false);
+        }
+        if memory.resisted.is_empty() && memory.countered.is_empty() {
+            log!(info, "The hive never got a read on the squad.", true);
+        } else {
+            log!(info, "The hive adapts to whatever works; vary damage types and tactics to stay ahead of it.", true);
+        }
+    }
+
+    pub fn link(&self) -> &HiveLink { &self.link }
+
+    pub fn link_mut(&mut self) -> &mut HiveLink { &mut self.link }
+
+    /// Drops the last wave's network; call before a new wave is linked.
+    pub fn reset_link(&mut self) { self.link = HiveLink::default(); }
+
+    /// Rewires the network around the living Hiveminds, each member joining the closest one.
+    /// Returns the old network so the caller can see which hubs have gone dark.
+    pub fn relink(&mut self, wave: &[Bug], tiles: impl Fn(usize, usize) -> Option<u32>) -> HiveLink {
+        let hubs: Vec<usize> = (0..wave.len()).filter(|&bi| wave[bi].is_alive() && wave[bi].is_hivemind()).collect();
+        let mut links: Vec<(usize, usize)> = (0..wave.len())
+            .filter(|&bi| wave[bi].is_alive() && wave[bi].can_link())
+            .filter_map(|bi| hubs.iter()
+                .min_by_key(|&&h| tiles(h, bi).unwrap_or(u32::MAX))
+                .map(|&h| (h, bi)))
+            .collect();
+        links.sort();
+        let focus = self.link.focus;
+        std::mem::replace(&mut self.link, HiveLink { links, focus })
+    }
+
+    pub fn alert(&self) -> &HiveAlert { &self.alert }
+
+    pub fn alert_mut(&mut self) -> &mut HiveAlert { &mut self.alert }
+
+    pub fn difficulty(&self) -> &Difficulty { &self.difficulty }
+
+    /// Sets how deep the squad is, which decides what the hive sends from here on.
+    pub fn set_depth(&mut self, depth: usize, squad: usize) {
+        self.difficulty = Difficulty::new(depth, squad);
+    }
+
+    /// A full wave for the current depth.
+    pub fn spawn_wave(&self) -> Vec<Bug> {
+        self.spawn_test_wave(self.difficulty.wave_size)
+    }
+
+    /// Tops a wave up with extra bugs the more alert the hive is.
+    pub fn reinforce_for_alert(&self, wave: &mut Vec<Bug>) {
+        let extra = self.alert.wave_bonus();
+        if extra > 0 {
+            log!(info, format!("The hive is {:?}: {extra} extra bugs join the wave", self.alert.tier()), false);
+            wave.extend(self.spawn_test_wave(extra));
+        }
+    }
+
+    /// Rolls whether a chamber on the way out has been repopulated, and with how many bugs.
+    pub fn extraction_respawn(&self, rng: &mut impl Rng) -> Option<Vec<Bug>> {
+        (rng.random::<f32>() < self.alert.respawn_chance())
+            .then(|| self.spawn_test_wave(2 + self.alert.wave_bonus()))
+    }
+
+    /// Whether the wave is lying in wait: the chamber's odds, better for every bug that fights from hiding.
+    pub fn roll_ambush(&self, chamber_chance: f32, wave: &[Bug], rng: &mut impl Rng) -> bool {
+        if chamber_chance <= 0.0 { return false; }
+        let ambushers = wave.iter().filter(|b| b.is_ambusher()).count() as f32;
+        rng.random::<f32>() < (chamber_chance + ambushers * 0.1).min(0.9)
+    }
+
+    /// Mid-fight reinforcements: whatever the chamber's nests push out this round, plus a chance the
+    /// hive sends a straggler on its own, more often the louder the squad has been.
+    pub fn reinforcements(&self, due: usize, rng: &mut impl Rng) -> Vec<Bug> {
+        let called = if rng.random::<f32>() < self.alert.reinforcement_chance() { 1 } else { 0 };
+        self.spawn_test_wave(due + called)
+    }
+
+    /// Works out which family synergies hold this round and hands each covered bug its bonus.
+    /// `tiles` measures the distance between two bugs in the wave. Returns the synergies in play.
+    pub fn apply_synergies(&self, wave: &mut [Bug], tiles: impl Fn(usize, usize) -> Option<u32>) -> Vec<&'static FamilySynergy> {
+        for b in wave.iter_mut() { b.synergy = SynergyBonus::default(); }
+        let mut active = Vec::new();
+        for syn in FAMILY_SYNERGIES {
+            let members: Vec<usize> = wave.iter()
+                .enumerate()
+                .filter(|(_, b)| b.is_alive() && b.family == syn.family)
+                .map(|(bi, _)| bi)
+                .collect();
+            let covered: Vec<usize> = match syn.anchor {
+                None => members,
+                Some((species, radius)) => members.iter()
+                    .copied()
+                    .filter(|&m| members.iter().any(|&a| wave[a].species == species && tiles(a, m).is_some_and(|t| t <= radius)))
+                    .collect(),
+            };
+            if covered.len() < syn.min_members { continue; }
+            for bi in covered { wave[bi].synergy = syn.bonus; }
+            active.push(syn);
+        }
+        active
+    }
+
+    /// Brood Chambers sometimes raise a mini-boss: the wave's toughest bug is crowned.
+    pub fn crown_miniboss(&self, wave: &mut [Bug], chance: f32, rng: &mut impl Rng) {
+        if rng.random::<f32>() >= chance { return; }
+        if let Some(boss) = wave.iter_mut().max_by_key(|b| b.max_hp()) {
+            boss.promote(BugRank::MiniBoss);
+            log!(info, format!("{} A {} mini-boss leads the wave!", boss.badge(), boss.name), false);
+        }
     }
 
     pub fn spawn_test_wave(&self, count: usize) -> Vec<Bug> {
-        use BugSpecies::*;
-        let species_pool = vec![
-            Snapper, Maw, Noodle, Priest, Skitter, Leaper,
-            Sporebelly, Fleshcrawler, Blinker, Skulker, Tornaut,
-        ];
+        let species_pool = self.difficulty.species_pool();
 
         let mut rng = rand::rng();
 
         (0..count)
             .map(|_| {
                 let species = *species_pool.choose(&mut rng).unwrap();
-                Bug::new(species)
+                let mut bug = Bug::new(species, &self.difficulty);
+                if rng.random::<f32>() < self.difficulty.elite_chance { bug.promote(BugRank::Elite); }
+                bug
             })
             .collect()
     }
@@ -458,6 +1180,9 @@ impl Broodmother {
         for (i, bug) in wave.iter().enumerate() {
             log!(info, format!("--- BUG {} ---", i + 1), false);
             log!(info, format!("Species: {:?} ({})", bug.species, bug.name), false);
+            if bug.is_elite() {
+                log!(info, format!("Rank: {} {:?}", bug.badge(), bug.rank), false);
+            }
             log!(info, format!("Family: {}", bug.family), false);
             log!(info, format!("Class: {:?}", bug.class), false);
             log!(info, format!("Tactic: {:?}", bug.tactic), false);
@@ -471,8 +1196,23 @@ impl Broodmother {
         bug.attack(target);
     }
 
-    pub fn bug_attacked(&self, bug: &mut Bug, dmg: u32, hp_dmg: u32, ap_dmg: u32) {
-        bug.take_damage(dmg, hp_dmg, ap_dmg);
+    /// Damage the squad deals with its own weapons and gear is remembered by type.
+    pub fn bug_attacked(&mut self, bug: &mut Bug, event: &DamageEvent) -> Mitigated {
+        let taken = bug.take_hit(event);
+        if matches!(event.source, DamageSource::Weapon(_) | DamageSource::Gear(_)) {
+            HiveMemory::bump(&mut self.memory.damage, event.profile.damage_type, taken.hp_loss + taken.ap_loss);
+        }
+        taken
+    }
+
+    /// An effect from the squad's gear landing on a bug, with any damage it carries remembered like a hit.
+    pub fn bug_affected(&mut self, bug: &mut Bug, effect: Effect, source: DamageSource) -> Mitigated {
+        if bug.rank.immune_to(&effect) { return Mitigated::default(); }
+        let taken = effect.damage_profile()
+            .map(|profile| self.bug_attacked(bug, &DamageEvent::new(profile, source)))
+            .unwrap_or_default();
+        bug.apply_effect(effect);
+        taken
     }
 
     pub fn rebalance_wave(&mut self, wave: &mut [Bug], dmg_factor: f32) {
@@ -481,3 +1221,94 @@ impl Broodmother {
         }
     }
 }
+
+#[cfg(test)]
+impl Bug {
+    /// A freshly hatched bug of the first species with the given class, for tests in other modules.
+    pub(crate) fn of_class(class: BugClass) -> Self {
+        let species = SPECIES_DEPTH.iter().map(|(s, _)| *s)
+            .find(|s| Self::get_species_info(*s).0 == class)
+            .expect("every class has a species");
+        Bug::new(species, &Difficulty::new(1, 3))
+    }
+
+    /// Wired into the nearest Hivemind whatever it rolled.
+    pub(crate) fn hivelinked(mut self) -> Self {
+        self.traits.hivelink = true;
+        self.flaws.outcast = false;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::armory::Area;
+
+    fn bug(species: BugSpecies) -> Bug {
+        Bug::new(species, &Difficulty::new(1, 3))
+    }
+
+    fn linkable(species: BugSpecies) -> Bug {
+        bug(species).hivelinked()
+    }
+
+    fn near(_: usize, _: usize) -> Option<u32> { Some(1) }
+
+    #[test]
+    fn relink_joins_members_to_hivemind_and_skips_outcasts() {
+        let mut bm = Broodmother::new();
+        let mut outcast = linkable(BugSpecies::Skitter);
+        outcast.flaws.outcast = true;
+        let wave = vec![linkable(BugSpecies::Skitter), outcast, bug(BugSpecies::Priest)];
+        bm.relink(&wave, near);
+        assert_eq!(bm.link().hubs(), vec![2]);
+        assert_eq!(bm.link().members_of(2), vec![0]);
+        assert!(!bm.link().is_linked(1));
+    }
+
+    #[test]
+    fn killing_the_hub_severs_its_members() {
+        let mut bm = Broodmother::new();
+        let mut wave = vec![linkable(BugSpecies::Skitter), linkable(BugSpecies::Leaper), bug(BugSpecies::Priest)];
+        bm.relink(&wave, near);
+        let hp = wave[2].hp();
+        wave[2].lose_hp(hp);
+        let old = bm.relink(&wave, near);
+        assert_eq!(old.severed(&wave), vec![(2, 0), (2, 1)]);
+        assert!(bm.link().hubs().is_empty());
+    }
+
+    #[test]
+    fn smaller_next_wave_does_not_reach_into_old_links() {
+        let mut bm = Broodmother::new();
+        let mut big = vec![
+            linkable(BugSpecies::Skitter), linkable(BugSpecies::Skitter), linkable(BugSpecies::Skitter),
+            linkable(BugSpecies::Skitter), bug(BugSpecies::Priest),
+        ];
+        bm.relink(&big, near);
+        bm.link_mut().set_focus(Some(0));
+        let hp = big[4].hp();
+        big[4].lose_hp(hp);
+
+        // Left over from the bigger wave: out-of-range pairs are skipped, not indexed
+        let small = vec![bug(BugSpecies::Snapper), bug(BugSpecies::Maw)];
+        assert!(bm.link().severed(&small).is_empty());
+
+        bm.reset_link();
+        assert!(bm.link().focus().is_none());
+        let old = bm.relink(&small, near);
+        assert!(old.severed(&small).is_empty());
+        assert!(bm.link().hubs().is_empty());
+    }
+
+    #[test]
+    fn gear_effect_damage_is_remembered() {
+        let mut bm = Broodmother::new();
+        let mut b = bug(BugSpecies::Skitter);
+        let hp = b.hp();
+        bm.bug_affected(&mut b, Effect::AoE { dmg: 10, aoe: Area::Immediate }, DamageSource::Gear("Frag Grenade"));
+        assert!(b.hp() < hp);
+        assert_eq!(bm.memory.damage, vec![(DamageType::Explosive, hp - b.hp())]);
+    }
+}
@@ -6,6 +6,8 @@
 use rand::Rng;
 use rand::rngs::SmallRng;
 
+use crate::armory::{DamageType, Distance};
+
 #[derive(Copy, Debug, Clone)]
 pub enum HitOutcome { Miss, Graze, Hit, Crit }
 
@@ -14,24 +16,99 @@ pub struct HitInputs {
     defender_evasion: f32,
     cover: Option<f32>,
     situational: Option<f32>,
+    range: Option<f32>,
 }
 
 impl HitInputs {
-    pub fn new(attacker_acc: f32, defender_evasion: f32, cover: Option<f32>, situational: Option<f32>) -> Self {
-        HitInputs { attacker_acc, defender_evasion, cover, situational }
+    pub fn new(attacker_acc: f32, defender_evasion: f32, cover: Option<f32>, situational: Option<f32>, range: Option<f32>) -> Self {
+        HitInputs { attacker_acc, defender_evasion, cover, situational, range }
+    }
+}
+
+/// How a weapon (or bug) behaves across range bands.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RangeProfile {
+    #[default] Standard,
+    Melee,       // Close or nothing
+    Scattershot, // shotguns: damage drops off every band out
+    Marksman,    // ScopedRifle/Railgun: rewarded for keeping their distance
+}
+
+/// Range-band multipliers for one attacker/target pairing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RangeMods {
+    pub usable: bool,
+    pub accuracy: f32,
+    pub damage: f32,
+}
+
+/// What a single hit carries before it meets the target's armor.
+#[derive(Default, Copy, Clone, Debug, PartialEq)]
+pub struct DamageProfile {
+    pub damage_type: DamageType,
+    pub hp: u32,     // damage aimed at health
+    pub ap: u32,     // damage aimed at armor
+    pub pierce: u8,  // percent of `hp` that skips armor entirely (100 = IgnoreArmor)
+}
+
+impl DamageProfile {
+    pub fn new(damage_type: DamageType, hp: u32, ap: u32, pierce: u8) -> Self {
+        DamageProfile { damage_type, hp, ap, pierce: pierce.min(100) }
+    }
+
+    /// Straight to HP, armor untouched (frag grenades, IgnoreArmor).
+    pub fn true_damage(damage_type: DamageType, hp: u32) -> Self {
+        Self::new(damage_type, hp, 0, 100)
+    }
+
+    pub fn scaled(self, mult: f32) -> Self {
+        let to = |x: u32| ((x as f32) * mult) as u32;
+        DamageProfile { hp: to(self.hp), ap: to(self.ap), ..self }
+    }
+
+    pub fn is_zero(&self) -> bool { self.hp == 0 && self.ap == 0 }
+}
+
+/// Who or what dealt a hit.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum DamageSource {
+    Weapon(&'static str),
+    Bug(&'static str),
+    Gear(&'static str),
+    Effect,
+    Environment,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DamageEvent {
+    pub profile: DamageProfile,
+    pub source: DamageSource,
+}
+
+impl DamageEvent {
+    pub fn new(profile: DamageProfile, source: DamageSource) -> Self {
+        DamageEvent { profile, source }
     }
 }
 
+/// What a hit actually took off the target after armor had its say.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Mitigated {
+    pub hp_loss: u32,
+    pub ap_loss: u32,
+}
+
 pub struct DamageInputs {
-    pub base_dmg: u32, pub base_hp: u32, pub base_ap: u32,
-    pub attacker_dmg_mod: f32,   // trooper/big mod (1.0 = neutral)
+    pub profile: DamageProfile,
+    pub attacker_dmg_mod: f32,   // trooper/bug mod (1.0 = neutral)
+    pub range_mult: f32,         // from RangeMods::damage
     pub outcome_mults: [f32; 4], // per HitOutcome: [miss,graze,hit,crit]
 }
 
 impl DamageInputs {
-    pub fn new(base_dmg: u32, base_hp: u32, base_ap: u32, attacker_dmg_mod: f32) -> Self {
+    pub fn new(profile: DamageProfile, attacker_dmg_mod: f32, range_mult: f32) -> Self {
         let outcome_mults = [0.0, 0.4, 1.0, 1.5];
-        DamageInputs { base_dmg, base_hp, base_ap, attacker_dmg_mod, outcome_mults }
+        DamageInputs { profile, attacker_dmg_mod, range_mult, outcome_mults }
     }
 }
 
@@ -51,7 +128,7 @@ impl AttackContext {
 
 pub struct AttackResult {
     pub outcome: HitOutcome,
-    pub final_dmg: (u32,u32,u32),
+    pub final_dmg: DamageProfile,
     pub hit_prob_used: f32,
     pub base_p: f32,
     pub pity_lift: f32,
@@ -65,7 +142,7 @@ impl Joker {
     }
 
     pub fn hit_probability(h: &HitInputs, scale: f32) -> f32 {
-        let atk = h.attacker_acc * h.situational.unwrap_or(1.0);
+        let atk = h.attacker_acc * h.situational.unwrap_or(1.0) * h.range.unwrap_or(1.0);
         let def = h.defender_evasion * h.cover.unwrap_or(1.0);
         let score = atk - def;
         1.0 / (1.0 + (-score / scale).exp())
@@ -111,18 +188,77 @@ impl Joker {
             HitOutcome::Crit  => ctx.dmg.outcome_mults[3],
         };
 
-        let scale = ctx.dmg.attacker_dmg_mod * mult;
-        let to = |x: u32| ((x as f32) * scale) as u32;
+        let scale = ctx.dmg.attacker_dmg_mod * ctx.dmg.range_mult * mult;
 
         AttackResult {
             outcome,
-            final_dmg: (to(ctx.dmg.base_dmg), to(ctx.dmg.base_hp), to(ctx.dmg.base_ap)),
+            final_dmg: ctx.dmg.profile.scaled(scale),
             hit_prob_used: p,
             base_p,
             pity_lift: p - base_p,
         }
     }
 
+    /// Accuracy drops 10% per band when the target is closer than the weapon likes and 20% per band
+    /// past it; nothing fires more than one band beyond its preferred range. Shotguns lose a quarter
+    /// of their damage per band out, marksman rifles gain accuracy and damage at Normal/Far.
+    pub fn range_mods(preferred: Distance, profile: RangeProfile, tiles: u32) -> RangeMods {
+        let actual = Distance::from_tiles(tiles);
+        let (want, got) = (preferred.band() as i32, actual.band() as i32);
+
+        let usable = match profile {
+            RangeProfile::Melee => actual == Distance::Close,
+            _ => got <= want + 1,
+        };
+        let mut accuracy = match got - want {
+            d if d < 0 => 1.0 - 0.10 * (-d) as f32,
+            d => 1.0 - 0.20 * d as f32,
+        };
+        let mut damage = 1.0;
+
+        match (profile, actual) {
+            (RangeProfile::Scattershot, _) => damage = 1.0 - 0.25 * got as f32,
+            (RangeProfile::Marksman, Distance::Far) => { accuracy *= 1.15; damage = 1.2; },
+            (RangeProfile::Marksman, Distance::Normal) => accuracy *= 1.1,
+            _ => {},
+        }
+
+        RangeMods { usable, accuracy: accuracy.max(0.4), damage }
+    }
+
+    /// How hard a damage type chews through armor.
+    pub fn armor_factor(damage_type: DamageType) -> f32 {
+        match damage_type {
+            DamageType::Corrosive => 1.5,
+            DamageType::Explosive => 1.25,
+            DamageType::Burn => 0.75,
+            _ => 1.0,
+        }
+    }
+
+    /// The one armor rule both sides live by.
+    ///
+    /// The pierced share of `hp` lands on health no matter what. The rest is stopped by armor while
+    /// any is left; armor takes `ap` (scaled by type, reduced by pierce). If that breaks the armor,
+    /// the blocked HP damage spills through in proportion to how much of the armor hit was left over.
+    pub fn mitigate(profile: &DamageProfile, hp: u32, ap: u32) -> Mitigated {
+        let pierce = profile.pierce.min(100) as f32 / 100.0;
+        let through = ((profile.hp as f32) * pierce) as u32;
+        let blocked = profile.hp - through;
+        let ap_dmg = ((profile.ap as f32) * (1.0 - pierce) * Self::armor_factor(profile.damage_type)) as u32;
+
+        let (ap_loss, spill) = if ap == 0 {
+            (0, blocked)
+        } else if ap_dmg >= ap {
+            let leftover = 1.0 - (ap as f32 / ap_dmg as f32);
+            (ap, ((blocked as f32) * leftover) as u32)
+        } else {
+            (ap_dmg, 0)
+        };
+
+        Mitigated { hp_loss: (through + spill).min(hp), ap_loss }
+    }
+
     // tiny helpers
     #[inline]
     fn rank(o: HitOutcome) -> u8 { match o { HitOutcome::Miss=>0, HitOutcome::Graze=>1, HitOutcome::Hit=>2, HitOutcome::Crit=>3 } }
@@ -131,3 +267,89 @@ impl Joker {
     #[inline]
     fn worst(a: HitOutcome,b:HitOutcome)->HitOutcome{ if Self::rank(a)<=Self::rank(b) {a} else {b} }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ballistic(hp: u32, ap: u32, pierce: u8) -> DamageProfile {
+        DamageProfile::new(DamageType::Ballistic, hp, ap, pierce)
+    }
+
+    #[test]
+    fn armor_soaks_hit_while_it_holds() {
+        let m = Joker::mitigate(&ballistic(20, 10, 0), 100, 30);
+        assert_eq!(m, Mitigated { hp_loss: 0, ap_loss: 10 });
+    }
+
+    #[test]
+    fn breaking_armor_spills_remaining_share_into_hp() {
+        // 10 AP left against a 20 AP hit: half the hit was left over, so half the HP damage lands
+        let m = Joker::mitigate(&ballistic(20, 20, 0), 100, 10);
+        assert_eq!(m, Mitigated { hp_loss: 10, ap_loss: 10 });
+
+        let m = Joker::mitigate(&ballistic(20, 30, 0), 100, 10);
+        assert_eq!(m, Mitigated { hp_loss: 13, ap_loss: 10 });
+    }
+
+    #[test]
+    fn no_armor_takes_full_hp_damage() {
+        let m = Joker::mitigate(&ballistic(20, 10, 0), 100, 0);
+        assert_eq!(m, Mitigated { hp_loss: 20, ap_loss: 0 });
+    }
+
+    #[test]
+    fn armor_pierce_splits_hit() {
+        let m = Joker::mitigate(&ballistic(20, 10, 50), 100, 30);
+        assert_eq!(m, Mitigated { hp_loss: 10, ap_loss: 5 });
+    }
+
+    #[test]
+    fn ignore_armor_goes_straight_to_hp() {
+        let m = Joker::mitigate(&DamageProfile::true_damage(DamageType::Explosive, 25), 100, 40);
+        assert_eq!(m, Mitigated { hp_loss: 25, ap_loss: 0 });
+    }
+
+    #[test]
+    fn damage_type_scales_armor_damage() {
+        let acid = DamageProfile::new(DamageType::Corrosive, 20, 10, 0);
+        assert_eq!(Joker::mitigate(&acid, 100, 30), Mitigated { hp_loss: 0, ap_loss: 15 });
+
+        let fire = DamageProfile::new(DamageType::Burn, 20, 10, 0);
+        assert_eq!(Joker::mitigate(&fire, 100, 30), Mitigated { hp_loss: 0, ap_loss: 7 });
+    }
+
+    #[test]
+    fn hp_loss_never_exceeds_remaining_hp() {
+        let m = Joker::mitigate(&ballistic(50, 0, 0), 12, 0);
+        assert_eq!(m, Mitigated { hp_loss: 12, ap_loss: 0 });
+    }
+
+    #[test]
+    fn range_bands_shape_accuracy_and_damage() {
+        // Rifle (Normal) fired point blank: two bands too close
+        let m = Joker::range_mods(Distance::Normal, RangeProfile::Standard, 1);
+        assert_eq!((m.usable, m.accuracy, m.damage), (true, 0.8, 1.0));
+
+        // One band past preferred is a stretch, two is out
+        let m = Joker::range_mods(Distance::Near, RangeProfile::Standard, 5);
+        assert_eq!((m.usable, m.accuracy), (true, 0.8));
+        assert!(!Joker::range_mods(Distance::Near, RangeProfile::Standard, 7).usable);
+
+        assert!(!Joker::range_mods(Distance::Close, RangeProfile::Melee, 2).usable);
+
+        let m = Joker::range_mods(Distance::Close, RangeProfile::Scattershot, 3);
+        assert_eq!((m.usable, m.damage), (true, 0.75));
+
+        let m = Joker::range_mods(Distance::Far, RangeProfile::Marksman, 9);
+        assert_eq!((m.accuracy, m.damage), (1.15, 1.2));
+    }
+
+    #[test]
+    fn outcome_multiplier_scales_both_channels() {
+        let crit = ballistic(38, 34, 0).scaled(1.5);
+        assert_eq!((crit.hp, crit.ap), (57, 51));
+        let graze = ballistic(38, 34, 0).scaled(0.4);
+        assert_eq!((graze.hp, graze.ap), (15, 13));
+    }
+}
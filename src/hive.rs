@@ -13,17 +13,61 @@
 use rand::Rng;
 
 use crate::log;
-use crate::bugs::{Broodmother, Bug};
-use crate::troopers::Commander;
-use crate::armory::{Armory, Gear};
+use crate::bugs::Bug;
+use crate::armory::{DamageType, Deployable, Distance, Gear};
+use crate::combat::DamageProfile;
 
 // Globals
 
-#[derive(Clone, Debug, PartialEq)]
-enum ChamberType { Entrance, Tunnel, Clearing, BroodChamber, FoodStorage, Flooded, Collapsed, EggChamber}
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChamberType { Entrance, Tunnel, Clearing, BroodChamber, FoodStorage, Flooded, Collapsed, EggChamber}
+
+/// Something left burning or pooling on a tile that hurts whoever stands in it at the start of a round.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Hazard {
+    Fire { turns: u8 },
+    AcidPool { turns: u8 },
+}
+
+impl Hazard {
+    pub fn damage_profile(&self) -> DamageProfile {
+        match self {
+            Hazard::Fire { .. } => DamageProfile::new(DamageType::Burn, 8, 4, 0),
+            Hazard::AcidPool { .. } => DamageProfile::new(DamageType::Corrosive, 3, 6, 0),
+        }
+    }
+
+    /// One round older; None once it has burned out or soaked away.
+    fn tick(self) -> Option<Hazard> {
+        match self {
+            Hazard::Fire { turns } if turns > 1 => Some(Hazard::Fire { turns: turns - 1 }),
+            Hazard::AcidPool { turns } if turns > 1 => Some(Hazard::AcidPool { turns: turns - 1 }),
+            _ => None,
+        }
+    }
+}
+
+/// What a hit does to the terrain around it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Reaction { Ignite, CaveIn, AcidPool }
 
 #[derive(Clone, Debug)]
-enum Hazard {  }
+pub struct TerrainRule {
+    damage_type: DamageType,
+    /// None applies in every chamber.
+    chamber: Option<ChamberType>,
+    pub reaction: Reaction,
+    pub chance: f32,
+}
+
+static TERRAIN_RULES: &[TerrainRule] = &[
+    // Fungal growth and rotting stores go up like tinder
+    TerrainRule { damage_type: DamageType::Burn, chamber: Some(ChamberType::FoodStorage), reaction: Reaction::Ignite, chance: 0.6 },
+    TerrainRule { damage_type: DamageType::Burn, chamber: Some(ChamberType::BroodChamber), reaction: Reaction::Ignite, chance: 0.25 },
+    TerrainRule { damage_type: DamageType::Explosive, chamber: Some(ChamberType::Collapsed), reaction: Reaction::CaveIn, chance: 0.5 },
+    TerrainRule { damage_type: DamageType::Explosive, chamber: Some(ChamberType::Tunnel), reaction: Reaction::CaveIn, chance: 0.15 },
+    TerrainRule { damage_type: DamageType::Corrosive, chamber: None, reaction: Reaction::AcidPool, chance: 0.2 },
+];
 
 #[derive(Copy, Clone, Debug)]
 enum Boon {  }
@@ -34,6 +78,7 @@ struct ChamberInfo {
     name: &'static str,
     description: &'static str,
     flavor: &'static str,
+    grid: (u8, u8),
 }
 
 static CHAMBER_INFO: &[ChamberInfo] = &[
@@ -42,48 +87,56 @@ static CHAMBER_INFO: &[ChamberInfo] = &[
         name: "Entrance",
         description: "The entrance to the hive",
         flavor: "The Opening yaws before you, screeches, chitters, and a faint slithering noise can be heard from inside. The Hive awaits.",
+        grid: (6, 6),
     },
     ChamberInfo { 
         r#type: ChamberType::Tunnel,
         name: "Tunnel",
         description: "The standard connecting passage between chambers. Can contain minor hazards like acid drips or falling debris, and is the most common room type. Sometimes ambushes occur here.",
         flavor: "The tunnel quivers as you move, every echo doubled by the stone. Chitin crunches underfoot. The scent of ammonia lingers in the stale air.",
+        grid: (8, 3),
     },
     ChamberInfo { 
         r#type: ChamberType::Clearing,
         name: "Clearing",
         description: "A rare open space bathed in faint sunlight or bioluminescence. Functions as a safe(ish) room—may heal, regroup, or offer a partial refill.",
         flavor: "You step into a rare pocket of calm where the ceiling has thinned. A shaft of pale light spills down, chasing the shadows and your dread—for a moment, you remember what peace feels like.",
+        grid: (8, 8),
     },
     ChamberInfo { 
         r#type: ChamberType::BroodChamber,
         name: "Brood Chamber",
         description: "High spawn room, “bug nursery.” Walls may pulse with movement. Bug density is increased; expect waves. Sometimes houses a mini-boss.",
         flavor: "The walls are alive with motion. Bulging eggs and writhing larvae line the chamber, and the air vibrates with the faint sound of hatching. A swarm is never far behind.",
+        grid: (8, 6),
     },
     ChamberInfo { 
         r#type: ChamberType::FoodStorage,
         name: "Food Storage",
         description: "Bug larder: decaying corpses, fungal growths, sticky resin. Troopers may heal (if desperate), but risk sickness/debuffs. Common home to Fleshies, Sporebellies.",
         flavor: "Corpses—animal, human, bug—lie stacked and festering, some sealed in resin. Fungi bloom where flesh meets chitin. The stench makes your stomach turn.",
+        grid: (6, 6),
     },
     ChamberInfo { 
         r#type: ChamberType::Flooded,
         name: "Flooded Chamber",
         description: "A partially or fully submerged chamber—slows movement, may damage unprotected troopers, and is favored by Noodles. Some gear or classes may handle it better.",
         flavor: "Cold, dark water laps at your boots as you wade forward. Ripples vanish into the gloom. Something moves beneath the surface, swift and unseen.",
+        grid: (7, 5),
     },
     ChamberInfo { 
         r#type: ChamberType::Collapsed,
         name: "Collapsed Tunnel",
         description: "Passage choked by rubble or cave-ins. Must be cleared to proceed. May risk pinning or ambushes, and can become blocked on retreat.",
         flavor: "A tangle of broken stone and shattered resin blocks the way. Dust chokes the air with every step. The tunnel groans as if it might collapse again at any moment",
+        grid: (6, 3),
     },
     ChamberInfo { 
         r#type: ChamberType::EggChamber,
         name: "Egg Chamber",
         description: "The final room; the Queen’s lair. Unique boss fight. Disturbing eggs may trigger mass spawns or hazards.",
         flavor: "A vast chamber unfolds before you, crowded with throbbing egg sacs and the crawling shapes of new life. The Queen herself towers above all—a living nightmare in flesh and shell.",
+        grid: (10, 8),
     },
 ];
 
@@ -114,25 +167,650 @@ static CHAMBER_WEIGHTS: &[ChamberWeight] = &[
     ChamberWeight { r#type: ChamberType::Collapsed, weight: 1, possible_neighbors: &[ChamberType::Tunnel] },
 ];
 
+/// Digging needed to get through a fresh cave-in, in trooper-turns.
+const RUBBLE_WORK: u32 = 12;
+
+// Odds the bugs are lying in wait when the squad walks in.
+static AMBUSH_CHANCE: &[(ChamberType, f32)] = &[
+    (ChamberType::Tunnel, 0.3),
+    (ChamberType::Collapsed, 0.2),
+    (ChamberType::Flooded, 0.1),
+];
+
 static REQ_CHAMBERS: &[(ChamberType, usize, bool)] = &[
     (ChamberType::Clearing, 1, false),
     (ChamberType::Flooded, 1, false),
     (ChamberType::FoodStorage, 1, true),
 ];
 
+// ============ Fog of War =================
+
+/// How much the squad knows about a chamber.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Visibility { #[default] Hidden, Adjacent, Scouted, Visited }
+
+/// A headcount of the chamber as of some turn.
+#[derive(Debug, Copy, Clone)]
+pub struct Sighting {
+    pub bugs: usize,
+    pub nests: usize,
+    pub turn: usize,
+}
+
+#[derive(Default, Debug, Copy, Clone)]
+pub struct Intel {
+    pub visibility: Visibility,
+    pub last_seen: Option<Sighting>,
+}
+
+// ============ Hive Alert =================
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertTier { Calm, Wary, Alerted, Frenzied }
+
+/// Hive-wide awareness of the squad. Noise piles up during a round and settles into the alert level
+/// at the end of it; quiet rounds let the hive calm down again.
+#[derive(Default, Debug, Clone)]
+pub struct HiveAlert {
+    level: f32,
+    noise: f32,
+}
+
+impl HiveAlert {
+    const MAX: f32 = 100.0;
+    // The hive notices the squad more the longer it lingers
+    const TIME_PER_ROUND: f32 = 0.5;
+    const QUIET_ROUND: f32 = 2.0;
+    const QUIET_DECAY: f32 = 3.0;
+    const NOISE_WEIGHT: f32 = 0.1;
+
+    pub fn new() -> Self { Self::default() }
+
+    pub fn level(&self) -> f32 { self.level }
+
+    pub fn tier(&self) -> AlertTier {
+        match self.level {
+            l if l < 25.0 => AlertTier::Calm,
+            l if l < 50.0 => AlertTier::Wary,
+            l if l < 75.0 => AlertTier::Alerted,
+            _ => AlertTier::Frenzied,
+        }
+    }
+
+    pub fn make_noise(&mut self, amount: f32) { self.noise += amount.max(0.0); }
+
+    /// Folds the round's noise into the alert level. Every round adds its time cost; a quiet one
+    /// decays by more than that. Returns the new tier if it changed.
+    pub fn settle_round(&mut self) -> Option<AlertTier> {
+        let before = self.tier();
+        let noise = if self.noise < Self::QUIET_ROUND {
+            -Self::QUIET_DECAY
+        } else {
+            self.noise * Self::NOISE_WEIGHT
+        };
+        self.level = (self.level + noise + Self::TIME_PER_ROUND).clamp(0.0, Self::MAX);
+        self.noise = 0.0;
+        (self.tier() != before).then(|| self.tier())
+    }
+
+    /// Extra bugs per wave: one for every tier above Calm.
+    pub fn wave_bonus(&self) -> usize { self.tier() as usize }
+
+    /// Per-round chance of reinforcements arriving mid-fight.
+    pub fn reinforcement_chance(&self) -> f32 { 0.01 + self.level / 1000.0 }
+
+    /// Chance each chamber on the way out has been repopulated.
+    pub fn respawn_chance(&self) -> f32 { (0.2 + self.level / 125.0).min(1.0) }
+}
+
+// ============ Tactical Grid =================
+
+/// Tile coordinates inside a chamber. `x` runs from the squad's edge (0) toward the bugs' edge.
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Position { pub x: u8, pub y: u8 }
+
+impl Position {
+    pub fn new(x: u8, y: u8) -> Self { Position { x, y } }
+
+    /// Tiles between two positions; diagonals count as one step.
+    pub fn tiles_to(&self, other: Position) -> u32 {
+        (self.x.abs_diff(other.x)).max(self.y.abs_diff(other.y)) as u32
+    }
+
+    pub fn distance_to(&self, other: Position) -> Distance {
+        Distance::from_tiles(self.tiles_to(other))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Occupant { Trooper(usize), Bug(usize), Deployable(usize) }
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CoverKind { Rubble, ResinPillar, EggSac, Corpse }
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CoverLevel { Half, Full }
+
+impl CoverLevel {
+    /// Multiplier on the defender's evasion, fed to HitInputs::cover.
+    pub fn evasion_mult(&self) -> f32 {
+        match self {
+            CoverLevel::Half => 1.3,
+            CoverLevel::Full => 1.6,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Cover {
+    pub kind: CoverKind,
+    pub level: CoverLevel,
+    pub hp: u32,
+}
+
+impl Cover {
+    pub fn new(kind: CoverKind) -> Self {
+        let (level, hp) = match kind {
+            CoverKind::Rubble => (CoverLevel::Full, 60),
+            CoverKind::ResinPillar => (CoverLevel::Full, 80),
+            CoverKind::EggSac => (CoverLevel::Half, 20),
+            CoverKind::Corpse => (CoverLevel::Half, 30),
+        };
+        Cover { kind, level, hp }
+    }
+
+    /// Corpses can be stepped over; everything else has to be walked around.
+    pub fn blocks_movement(&self) -> bool { self.kind != CoverKind::Corpse }
+}
+
+// What each chamber type tends to be cluttered with.
+static COVER_LAYOUT: &[(ChamberType, &[(CoverKind, u8)])] = &[
+    (ChamberType::Entrance, &[(CoverKind::Rubble, 1)]),
+    (ChamberType::Tunnel, &[(CoverKind::Rubble, 1), (CoverKind::Corpse, 1)]),
+    (ChamberType::Clearing, &[(CoverKind::ResinPillar, 2)]),
+    (ChamberType::BroodChamber, &[(CoverKind::EggSac, 3), (CoverKind::ResinPillar, 1)]),
+    (ChamberType::FoodStorage, &[(CoverKind::Corpse, 3), (CoverKind::ResinPillar, 1)]),
+    (ChamberType::Flooded, &[(CoverKind::ResinPillar, 1)]),
+    (ChamberType::Collapsed, &[(CoverKind::Rubble, 4)]),
+    (ChamberType::EggChamber, &[(CoverKind::EggSac, 4), (CoverKind::ResinPillar, 2)]),
+];
+
+// ============ Spawning =================
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum NestKind { Nest, EggCluster }
+
+/// Something the hive spawns out of. Stops producing bugs once destroyed.
+#[derive(Debug, Copy, Clone)]
+pub struct Nest {
+    pub kind: NestKind,
+    pub pos: Position,
+    hp: u32,
+}
+
+impl Nest {
+    pub fn new(kind: NestKind, pos: Position) -> Self {
+        let hp = match kind {
+            NestKind::Nest => 40,
+            NestKind::EggCluster => 20,
+        };
+        Nest { kind, pos, hp }
+    }
+
+    pub fn hp(&self) -> u32 { self.hp }
+
+    pub fn is_alive(&self) -> bool { self.hp > 0 }
+
+    /// Returns true if the hit destroyed it.
+    pub fn take_damage(&mut self, dmg: u32) -> bool {
+        if !self.is_alive() { return false; }
+        self.hp = self.hp.saturating_sub(dmg);
+        !self.is_alive()
+    }
+}
+
+/// How often a chamber pushes reinforcements mid-fight: every `cadence` rounds (0 = never), `batch`
+/// bugs per living nest.
+#[derive(Debug, Clone)]
+struct SpawnProfile {
+    r#type: ChamberType,
+    cadence: usize,
+    batch: usize,
+    nests: &'static [(NestKind, u8)],
+    /// Chance the chamber's wave is led by a mini-boss.
+    miniboss_chance: f32,
+    /// Chance a damaged egg cluster bursts open early.
+    hatch_chance: f32,
+}
+
+static SPAWN_PROFILES: &[SpawnProfile] = &[
+    SpawnProfile { r#type: ChamberType::BroodChamber, cadence: 3, batch: 1, nests: &[(NestKind::Nest, 2)], miniboss_chance: 0.5, hatch_chance: 0.0 },
+    SpawnProfile { r#type: ChamberType::EggChamber, cadence: 2, batch: 1, nests: &[(NestKind::Nest, 1), (NestKind::EggCluster, 3)], miniboss_chance: 0.0, hatch_chance: 0.3 },
+];
+
+// Share of each chamber's floor standing under water.
+static WATER_LEVEL: &[(ChamberType, f32)] = &[
+    (ChamberType::Flooded, 0.6),
+];
+
+#[derive(Default, Clone, Debug)]
+pub struct Tile {
+    pub occupant: Option<Occupant>,
+    pub cover: Option<Cover>,
+    pub water: bool,
+    pub hazard: Option<Hazard>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Grid {
+    width: u8,
+    height: u8,
+    tiles: Vec<Tile>,
+}
+
+impl Grid {
+    pub fn new(width: u8, height: u8) -> Self {
+        Grid { width, height, tiles: vec![Tile::default(); width as usize * height as usize] }
+    }
+
+    pub fn for_chamber(ctype: ChamberType) -> Self {
+        let (w, h) = Cartographer::get_chamber_info(ctype).grid;
+        let mut grid = Self::new(w, h);
+        grid.scatter_cover(ctype);
+        grid.flood(ctype);
+        grid
+    }
+
+    fn flood(&mut self, ctype: ChamberType) {
+        let Some(&(_, level)) = WATER_LEVEL.iter().find(|(ct, _)| *ct == ctype) else { return; };
+        let mut rng = rand::rng();
+        for tile in self.tiles.iter_mut() {
+            tile.water = rng.random::<f32>() < level;
+        }
+    }
+
+    /// Drops the chamber's cover into the middle columns, leaving both deployment edges clear.
+    fn scatter_cover(&mut self, ctype: ChamberType) {
+        let Some((_, layout)) = COVER_LAYOUT.iter().find(|(ct, _)| *ct == ctype) else { return; };
+        if self.width < 5 { return; }
+        let mut rng = rand::rng();
+        for &(kind, count) in layout.iter() {
+            for _ in 0..count {
+                for _ in 0..10 {
+                    let pos = Position::new(rng.random_range(2..self.width - 2), rng.random_range(0..self.height));
+                    if self.tile(pos).is_some_and(|t| t.cover.is_none()) {
+                        self.add_cover(pos, Cover::new(kind));
+                        break;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn size(&self) -> (u8, u8) { (self.width, self.height) }
+
+    pub fn in_bounds(&self, pos: Position) -> bool { pos.x < self.width && pos.y < self.height }
+
+    fn idx(&self, pos: Position) -> usize { pos.y as usize * self.width as usize + pos.x as usize }
+
+    pub fn tile(&self, pos: Position) -> Option<&Tile> {
+        self.in_bounds(pos).then(|| &self.tiles[self.idx(pos)])
+    }
+
+    pub fn tile_mut(&mut self, pos: Position) -> Option<&mut Tile> {
+        let idx = self.idx(pos);
+        self.in_bounds(pos).then(move || &mut self.tiles[idx])
+    }
+
+    pub fn occupant_at(&self, pos: Position) -> Option<Occupant> {
+        self.tile(pos).and_then(|t| t.occupant)
+    }
+
+    pub fn is_free(&self, pos: Position) -> bool {
+        self.tile(pos).is_some_and(|t| t.occupant.is_none() && !t.cover.is_some_and(|c| c.blocks_movement()))
+    }
+
+    // ---- Water ----
+
+    pub fn is_water(&self, pos: Position) -> bool { self.tile(pos).is_some_and(|t| t.water) }
+
+    pub fn in_water(&self, occ: Occupant) -> bool { self.position_of(occ).is_some_and(|p| self.is_water(p)) }
+
+    /// Wading costs double.
+    pub fn move_cost(&self, pos: Position) -> u32 { if self.is_water(pos) { 2 } else { 1 } }
+
+    /// Everyone standing in water, for anything that travels through it.
+    pub fn submerged(&self) -> Vec<Occupant> {
+        self.tiles.iter().filter(|t| t.water).filter_map(|t| t.occupant).collect()
+    }
+
+    // ---- Hazards ----
+
+    /// Water puts fires out before they start.
+    pub fn set_hazard(&mut self, pos: Position, hazard: Hazard) {
+        if matches!(hazard, Hazard::Fire { .. }) && self.is_water(pos) { return; }
+        if let Some(tile) = self.tile_mut(pos) { tile.hazard = Some(hazard); }
+    }
+
+    pub fn hazard_at(&self, pos: Position) -> Option<Hazard> { self.tile(pos).and_then(|t| t.hazard) }
+
+    /// Hazards with someone standing in them.
+    pub fn occupied_hazards(&self) -> Vec<(Occupant, Hazard)> {
+        self.tiles.iter().filter_map(|t| Some((t.occupant?, t.hazard?))).collect()
+    }
+
+    pub fn tick_hazards(&mut self) {
+        for t in self.tiles.iter_mut() { t.hazard = t.hazard.and_then(Hazard::tick); }
+    }
+
+    pub fn clear_hazards(&mut self) {
+        for t in self.tiles.iter_mut() { t.hazard = None; }
+    }
+
+    // ---- Cover ----
+
+    pub fn add_cover(&mut self, pos: Position, cover: Cover) {
+        if let Some(tile) = self.tile_mut(pos) { tile.cover = Some(cover); }
+    }
+
+    pub fn cover_at(&self, pos: Position) -> Option<Cover> {
+        self.tile(pos).and_then(|t| t.cover)
+    }
+
+    /// Best cover next to the defender that sits on the attacker's side of them. Attackers right on
+    /// top of the defender (Close) get around it.
+    pub fn cover_between(&self, attacker: Position, defender: Position) -> Option<(Position, Cover)> {
+        if attacker.tiles_to(defender) <= 1 { return None; }
+        self.area(defender, 1).into_iter()
+            .filter(|p| *p != defender && p.tiles_to(attacker) < defender.tiles_to(attacker))
+            .filter_map(|p| self.cover_at(p).map(|c| (p, c)))
+            .max_by_key(|(_, c)| c.level)
+    }
+
+    /// Chips cover; returns true if it crumbled.
+    pub fn damage_cover(&mut self, pos: Position, dmg: u32) -> bool {
+        let Some(tile) = self.tile_mut(pos) else { return false; };
+        let Some(cover) = tile.cover.as_mut() else { return false; };
+        cover.hp = cover.hp.saturating_sub(dmg);
+        if cover.hp == 0 { tile.cover = None; true } else { false }
+    }
+
+    pub fn destroy_cover(&mut self, pos: Position) -> Option<Cover> {
+        self.tile_mut(pos).and_then(|t| t.cover.take())
+    }
+
+    pub fn position_of(&self, occ: Occupant) -> Option<Position> {
+        self.tiles.iter()
+            .position(|t| t.occupant == Some(occ))
+            .map(|i| Position::new((i % self.width as usize) as u8, (i / self.width as usize) as u8))
+    }
+
+    pub fn place(&mut self, occ: Occupant, pos: Position) -> bool {
+        if !self.is_free(pos) { return false; }
+        self.remove(occ);
+        if let Some(tile) = self.tile_mut(pos) { tile.occupant = Some(occ); }
+        true
+    }
+
+    pub fn remove(&mut self, occ: Occupant) {
+        for t in self.tiles.iter_mut().filter(|t| t.occupant == Some(occ)) {
+            t.occupant = None;
+        }
+    }
+
+    pub fn tiles_between(&self, a: Occupant, b: Occupant) -> Option<u32> {
+        Some(self.position_of(a)?.tiles_to(self.position_of(b)?))
+    }
+
+    /// All in-bounds positions within `radius` tiles of `center`, center included.
+    pub fn area(&self, center: Position, radius: u32) -> Vec<Position> {
+        let r = radius as i32;
+        let mut out = Vec::new();
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let (x, y) = (center.x as i32 + dx, center.y as i32 + dy);
+                if x < 0 || y < 0 { continue; }
+                let pos = Position::new(x as u8, y as u8);
+                if self.in_bounds(pos) { out.push(pos); }
+            }
+        }
+        out
+    }
+
+    /// Lines `count` occupants up column by column from one edge of the grid.
+    fn deploy(&mut self, count: usize, from_left: bool, make: impl Fn(usize) -> Occupant) {
+        let mut placed = 0;
+        let cols: Vec<u8> = if from_left { (0..self.width).collect() } else { (0..self.width).rev().collect() };
+        for x in cols {
+            for y in 0..self.height {
+                if placed == count { return; }
+                if self.place(make(placed), Position::new(x, y)) { placed += 1; }
+            }
+        }
+    }
+
+    pub fn deploy_squad(&mut self, count: usize) { self.deploy(count, true, Occupant::Trooper); }
+
+    pub fn deploy_wave(&mut self, count: usize) { self.deploy(count, false, Occupant::Bug); }
+
+    /// Drops `occ` on the closest free tile to `pos`. None if the grid is packed.
+    pub fn place_near(&mut self, occ: Occupant, pos: Position) -> Option<Position> {
+        let reach = self.width.max(self.height) as u32;
+        let spot = (0..=reach).find_map(|r| self.area(pos, r).into_iter()
+            .filter(|p| self.is_free(*p))
+            .min_by_key(|p| p.tiles_to(pos)))?;
+        self.place(occ, spot);
+        Some(spot)
+    }
+
+    /// Walks `occ` up to `steps` worth of movement toward `target`, stopping once adjacent or blocked.
+    /// Returns how many tiles it actually moved.
+    pub fn move_toward(&mut self, occ: Occupant, target: Position, steps: u32) -> u32 {
+        self.walk(occ, steps, |from, to| to.tiles_to(target) < from.tiles_to(target) && to != target)
+    }
+
+    /// Shoves `occ` up to `steps` tiles directly away from `from`.
+    pub fn push_away(&mut self, occ: Occupant, from: Position, steps: u32) -> u32 {
+        self.walk(occ, steps, |cur, to| to.tiles_to(from) > cur.tiles_to(from))
+    }
+
+    /// A single tile toward `target`, for callers that need to react to every tile entered.
+    pub fn step_toward(&mut self, occ: Occupant, target: Position) -> Option<Position> {
+        self.step(occ, |from, to| to.tiles_to(target) < from.tiles_to(target) && to != target)
+    }
+
+    fn step(&mut self, occ: Occupant, better: impl Fn(Position, Position) -> bool) -> Option<Position> {
+        let cur = self.position_of(occ)?;
+        let next = self.area(cur, 1).into_iter()
+            .filter(|p| self.is_free(*p) && better(cur, *p))
+            .min_by_key(|p| cur.tiles_to(*p) + if p.x != cur.x && p.y != cur.y { 1 } else { 0 })?;
+        self.place(occ, next);
+        Some(next)
+    }
+
+    fn walk(&mut self, occ: Occupant, steps: u32, better: impl Fn(Position, Position) -> bool) -> u32 {
+        let (mut moved, mut spent) = (0, 0);
+        while spent < steps {
+            let Some(pos) = self.step(occ, &better) else { break; };
+            moved += 1;
+            spent += self.move_cost(pos);
+        }
+        moved
+    }
+
+    /// Empties every tile of combatants; cover stays where it is.
+    pub fn clear(&mut self) {
+        for t in self.tiles.iter_mut() { t.occupant = None; }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Chamber {
     id: usize,
     r#type: ChamberType,
     neighbors: Vec<usize>,
+    /// Chambers between this one and the Entrance.
+    depth: usize,
+    grid: Grid,
+    deployed: Vec<Deployable>,
+    nests: Vec<Nest>,
+    /// Digging left before the way through is open; zero means passable.
+    rubble: u32,
+    /// Bugs waiting in the chamber for the squad to arrive.
+    bugs: usize,
+    intel: Intel,
     // state: ChamberState,
 }
 
-pub struct Cartographer;
+impl Chamber {
+    pub fn id(&self) -> usize { self.id }
+
+    pub fn chamber_type(&self) -> ChamberType { self.r#type }
+
+    pub fn depth(&self) -> usize { self.depth }
+
+    pub fn name(&self) -> &'static str { Cartographer::get_chamber_info(self.r#type).name }
+
+    pub fn intel(&self) -> Intel { self.intel }
+
+    /// What the squad would know about the chamber from a good look at it right now.
+    fn sighting(&self, turn: usize) -> Sighting {
+        Sighting { bugs: self.bugs, nests: self.live_nests(), turn }
+    }
+
+    /// What's known, in one line: nothing, the type, or the type plus the latest headcount and terrain.
+    pub fn describe(&self) -> String {
+        let name = self.name();
+        match (self.intel.visibility, self.intel.last_seen) {
+            (Visibility::Hidden, _) => "???".to_string(),
+            (Visibility::Adjacent, _) | (_, None) => name.to_string(),
+            (vis, Some(seen)) => {
+                let mut terrain = Vec::new();
+                if self.grid.tiles.iter().any(|t| t.water) { terrain.push("flooded"); }
+                if self.is_blocked() { terrain.push("blocked"); }
+                if seen.nests > 0 { terrain.push("nests"); }
+                let when = if vis == Visibility::Visited { "last seen" } else { "scouted" };
+                let terrain = if terrain.is_empty() { String::new() } else { format!(" ({})", terrain.join(", ")) };
+                format!("{name}: {} bugs, {when} turn {}{terrain}", seen.bugs, seen.turn)
+            },
+        }
+    }
+
+    pub fn ambush_chance(&self) -> f32 {
+        AMBUSH_CHANCE.iter().find(|(ct, _)| *ct == self.r#type).map_or(0.0, |(_, c)| *c)
+    }
+
+    pub fn grid(&self) -> &Grid { &self.grid }
+
+    pub fn grid_mut(&mut self) -> &mut Grid { &mut self.grid }
+
+    pub fn deployed(&self) -> &[Deployable] { &self.deployed }
+
+    pub fn deployed_mut(&mut self) -> &mut [Deployable] { &mut self.deployed }
+
+    /// Places gear in the chamber, returning its index. Standing deployables take the tile.
+    pub fn deploy(&mut self, item: Deployable) -> usize {
+        let idx = self.deployed.len();
+        if item.blocks_tile() { self.grid.place(Occupant::Deployable(idx), item.pos); }
+        self.deployed.push(item);
+        idx
+    }
+
+    /// Active tile-bound deployable lying at `pos` (mines, tripwires, traps).
+    pub fn trap_at(&self, pos: Position) -> Option<usize> {
+        self.deployed.iter().position(|d| d.pos == pos && !d.blocks_tile() && d.is_active())
+    }
+
+    /// Drops anything broken, expired or spent from the grid. Indices stay stable.
+    pub fn clear_inactive(&mut self) {
+        for (i, d) in self.deployed.iter().enumerate().filter(|(_, d)| !d.is_active()) {
+            if d.blocks_tile() { self.grid.remove(Occupant::Deployable(i)); }
+        }
+    }
+
+    pub fn clear_deployed(&mut self) {
+        for i in 0..self.deployed.len() { self.grid.remove(Occupant::Deployable(i)); }
+        self.deployed.clear();
+    }
+
+    // ---- Cave-ins ----
+
+    pub fn is_blocked(&self) -> bool { self.rubble > 0 }
+
+    pub fn rubble(&self) -> u32 { self.rubble }
+
+    pub fn collapse(&mut self) { self.rubble = RUBBLE_WORK; }
+
+    /// More rock down on top of whatever was already there.
+    pub fn add_rubble(&mut self, work: u32) { self.rubble += work; }
+
+    /// Shifts `work` worth of rubble. Returns true once the way is open.
+    pub fn dig(&mut self, work: u32) -> bool {
+        self.rubble = self.rubble.saturating_sub(work);
+        !self.is_blocked()
+    }
+
+    // ---- Spawning ----
+
+    fn spawn_profile(&self) -> Option<&'static SpawnProfile> {
+        SPAWN_PROFILES.iter().find(|sp| sp.r#type == self.r#type)
+    }
+
+    /// Nests go along the back wall, on the bugs' side of the chamber.
+    fn build_nests(&mut self) {
+        let Some(profile) = self.spawn_profile() else { return; };
+        let (w, h) = self.grid.size();
+        let mut rng = rand::rng();
+        for &(kind, count) in profile.nests {
+            for _ in 0..count {
+                let pos = Position::new(w - 1 - rng.random_range(0..2.min(w)), rng.random_range(0..h));
+                if self.nests.iter().all(|n| n.pos != pos) { self.nests.push(Nest::new(kind, pos)); }
+            }
+        }
+    }
+
+    pub fn nests(&self) -> &[Nest] { &self.nests }
+
+    pub fn nests_mut(&mut self) -> &mut [Nest] { &mut self.nests }
+
+    pub fn live_nests(&self) -> usize { self.nests.iter().filter(|n| n.is_alive()).count() }
+
+    /// Bugs due to crawl out at the start of `round`; zero between cadence ticks or once every nest is gone.
+    pub fn spawns_due(&self, round: usize) -> usize {
+        let Some(profile) = self.spawn_profile() else { return 0; };
+        if profile.cadence == 0 || round <= 1 || !round.is_multiple_of(profile.cadence) { return 0; }
+        profile.batch * self.live_nests()
+    }
+
+    pub fn miniboss_chance(&self) -> f32 { self.spawn_profile().map_or(0.0, |sp| sp.miniboss_chance) }
+
+    pub fn hatch_chance(&self) -> f32 { self.spawn_profile().map_or(0.0, |sp| sp.hatch_chance) }
+
+    /// Where new bugs come from: living nests, or the far edge in chambers without any.
+    pub fn spawn_points(&self) -> Vec<Position> {
+        let live: Vec<Position> = self.nests.iter().filter(|n| n.is_alive()).map(|n| n.pos).collect();
+        if !live.is_empty() { return live; }
+        let (w, h) = self.grid.size();
+        (0..h).map(|y| Position::new(w - 1, y)).collect()
+    }
+}
+
+pub struct Cartographer {
+    chambers: Vec<Chamber>,
+    current: usize,
+}
 
 impl Cartographer {
     pub fn new() -> Cartographer {
-        Cartographer
+        Cartographer { chambers: Vec::new(), current: 0 }
+    }
+
+    /// How the chamber's terrain answers a hit of this damage type, if it does at all.
+    pub fn terrain_rule(ctype: ChamberType, dtype: DamageType) -> Option<&'static TerrainRule> {
+        TERRAIN_RULES.iter().find(|r| r.damage_type == dtype && r.chamber.is_none_or(|c| c == ctype))
+    }
+
+    fn get_chamber_info(ctype: ChamberType) -> &'static ChamberInfo {
+        CHAMBER_INFO.iter().find(|ci| ci.r#type == ctype).unwrap()
     }
 
     fn restrict_chambers(pool: &mut Vec<ChamberWeight>, ctype: &ChamberType) {
@@ -160,14 +838,14 @@ impl Cartographer {
         let mut roll = rng.random_range(0..total_weight);
         for cw in pool {
             if roll < cw.weight {
-                return cw.r#type.clone();
+                return cw.r#type;
             }
             roll -= cw.weight;
         }
-        pool[0].r#type.clone()
+        pool[0].r#type
     }
 
-    fn check_validity(chambers: &Vec<ChamberType>, ctype: &ChamberType, pos: usize) -> bool {
+    fn check_validity(chambers: &[ChamberType], ctype: &ChamberType, pos: usize) -> bool {
         if pos >=2
             && chambers[pos - 1] == *ctype
             && chambers[pos - 2] == *ctype
@@ -190,7 +868,7 @@ impl Cartographer {
         while count < num {
             let pos = rng.random_range(1..chambers.len() - 1);
             if Self::check_validity(chambers, &must_have, pos) {
-                chambers.insert(pos, must_have.clone());
+                chambers.insert(pos, must_have);
                 count += 1;
             }
         }
@@ -225,7 +903,7 @@ impl Cartographer {
         chambers.push(ChamberType::EggChamber);
 
         for (ctype, n, restrict) in REQ_CHAMBERS {
-            Self::guarantee_chambers(&mut chambers, ctype.clone(), *n);
+            Self::guarantee_chambers(&mut chambers, *ctype, *n);
             if *restrict {
                 Self::restrict_chambers(&mut picker_pool, ctype);
             }
@@ -237,7 +915,10 @@ impl Cartographer {
     fn build_chambers(ctypes: Vec<ChamberType>) -> Vec<Chamber> {
         let mut chambers = Vec::new();
         for (i, ctype) in ctypes.into_iter().enumerate() {
-            chambers.push( Chamber { id: i, r#type: ctype, neighbors: vec![]});
+            let mut chamber = Chamber { id: i, r#type: ctype, neighbors: vec![], depth: i, grid: Grid::for_chamber(ctype), deployed: Vec::new(), nests: Vec::new(), rubble: 0, bugs: 0, intel: Intel::default() };
+            chamber.build_nests();
+            if ctype == ChamberType::Collapsed { chamber.collapse(); }
+            chambers.push(chamber);
         }
         for i in 0..chambers.len() - 1 {
             chambers[i].neighbors.push(i + 1);
@@ -246,15 +927,185 @@ impl Cartographer {
         chambers
     }
 
-    pub fn log_chambers(chambers: Vec<Chamber>) {
-        for chamber in chambers {
-            log!(info, format!("Chamber {} ({:?}) connects to {:?}", chamber.id, chamber.r#type, chamber.neighbors), false);
+    // ---- Fog of war ----
+
+    /// Sets how many bugs wait in each chamber past the Entrance, in order.
+    pub fn populate(&mut self, counts: &[usize]) {
+        for (chamber, &bugs) in self.chambers.iter_mut().skip(1).zip(counts) {
+            chamber.bugs = bugs;
+        }
+    }
+
+    /// Records what's left of the current chamber's bugs once the squad has seen it.
+    pub fn record_bugs(&mut self, bugs: usize, turn: usize) {
+        let chamber = self.current_chamber_mut();
+        chamber.bugs = bugs;
+        chamber.intel.last_seen = Some(chamber.sighting(turn));
+    }
+
+    /// The squad sees where it's standing and the openings around it.
+    pub fn reveal_around(&mut self, turn: usize) {
+        let current = self.current;
+        let chamber = &mut self.chambers[current];
+        chamber.intel.visibility = Visibility::Visited;
+        chamber.intel.last_seen = Some(chamber.sighting(turn));
+        for n in chamber.neighbors.clone() {
+            let intel = &mut self.chambers[n].intel;
+            intel.visibility = intel.visibility.max(Visibility::Adjacent);
+        }
+    }
+
+    /// Reveals chambers up to `range` deeper than the squad, with a fresh headcount.
+    pub fn scout_ahead(&mut self, range: usize, turn: usize) -> usize {
+        let mut seen = 0;
+        let deeper = self.chambers[self.current].neighbors.iter().copied().filter(|&n| n > self.current).min();
+        let mut next = deeper;
+        while let Some(id) = next.filter(|_| seen < range) {
+            let chamber = &mut self.chambers[id];
+            chamber.intel.visibility = chamber.intel.visibility.max(Visibility::Scouted);
+            chamber.intel.last_seen = Some(chamber.sighting(turn));
+            seen += 1;
+            next = chamber.neighbors.iter().copied().filter(|&n| n > id).min();
+        }
+        seen
+    }
+
+    /// The map as the squad knows it.
+    pub fn log_known_map(&self) {
+        for chamber in self.chambers.iter().filter(|c| c.intel.visibility > Visibility::Hidden) {
+            let here = if chamber.id == self.current { " <- squad" } else { "" };
+            log!(info, format!("  [{}] {}{here}", chamber.id, chamber.describe()), false);
         }
     }
 
-    pub fn spawn_chambers(&self, count: usize) {
+    pub fn spawn_chambers(&mut self, count: usize) {
         let ctypes = Self::gen_ctype_list(count);
-        let chambers = Self::build_chambers(ctypes);
-        Self::log_chambers(chambers);
+        self.chambers = Self::build_chambers(ctypes);
+        self.current = 0;
+        self.reveal_around(0);
+        log!(info, format!("🗺️ The hive has {} chambers; only the way in is known", self.chambers.len()), false);
+        self.log_known_map();
+    }
+
+    pub fn current_chamber(&self) -> &Chamber { &self.chambers[self.current] }
+
+    pub fn current_chamber_mut(&mut self) -> &mut Chamber { &mut self.chambers[self.current] }
+
+    /// Steps the squad one chamber back toward the Entrance. None once they're out.
+    pub fn retreat(&mut self) -> Option<&mut Chamber> {
+        if self.current == 0 { return None; }
+        self.current = *self.chambers[self.current].neighbors.iter().min()?;
+        Some(&mut self.chambers[self.current])
+    }
+
+    /// Digs a side passage around the current chamber, linking its neighbors, and moves the squad into it.
+    pub fn detour(&mut self) -> &mut Chamber {
+        let around = self.current;
+        let id = self.chambers.len();
+        let neighbors = self.chambers[around].neighbors.clone();
+        for &n in neighbors.iter() { self.chambers[n].neighbors.push(id); }
+        let ctype = ChamberType::Tunnel;
+        let depth = self.chambers[around].depth;
+        self.chambers.push(Chamber { id, r#type: ctype, neighbors, depth, grid: Grid::for_chamber(ctype), deployed: Vec::new(), nests: Vec::new(), rubble: 0, bugs: 0, intel: Intel::default() });
+        self.current = id;
+        &mut self.chambers[id]
+    }
+
+    /// Moves the squad one chamber deeper, stopping at the Egg Chamber.
+    pub fn advance(&mut self) -> &mut Chamber {
+        self.current = (self.current + 1).min(self.chambers.len().saturating_sub(1));
+        &mut self.chambers[self.current]
+    }
+
+    pub fn grid(&self) -> &Grid { &self.current_chamber().grid }
+
+    pub fn grid_mut(&mut self) -> &mut Grid { &mut self.current_chamber_mut().grid }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rubble_wall(grid: &mut Grid, x: u8) {
+        for y in 0..grid.height { grid.add_cover(Position::new(x, y), Cover::new(CoverKind::Rubble)); }
+    }
+
+    #[test]
+    fn deploy_lines_squad_and_wave_up_on_opposite_edges() {
+        let mut grid = Grid::new(5, 3);
+        grid.deploy_squad(4);
+        grid.deploy_wave(2);
+        assert_eq!(grid.position_of(Occupant::Trooper(0)), Some(Position::new(0, 0)));
+        assert_eq!(grid.position_of(Occupant::Trooper(3)), Some(Position::new(1, 0)));
+        assert_eq!(grid.position_of(Occupant::Bug(0)), Some(Position::new(4, 0)));
+        assert_eq!(grid.position_of(Occupant::Bug(1)), Some(Position::new(4, 1)));
+    }
+
+    #[test]
+    fn step_toward_closes_in_and_stops_at_walls() {
+        let mut grid = Grid::new(5, 3);
+        let goal = Position::new(0, 1);
+        grid.place(Occupant::Trooper(0), goal);
+        grid.place(Occupant::Bug(0), Position::new(4, 1));
+        assert_eq!(grid.step_toward(Occupant::Bug(0), goal), Some(Position::new(3, 1)));
+
+        rubble_wall(&mut grid, 2);
+        assert_eq!(grid.step_toward(Occupant::Bug(0), goal), None);
+        assert_eq!(grid.position_of(Occupant::Bug(0)), Some(Position::new(3, 1)));
+    }
+
+    #[test]
+    fn step_toward_never_enters_the_target_tile() {
+        let mut grid = Grid::new(3, 1);
+        let goal = Position::new(0, 0);
+        grid.place(Occupant::Trooper(0), goal);
+        grid.place(Occupant::Bug(0), Position::new(1, 0));
+        assert_eq!(grid.step_toward(Occupant::Bug(0), goal), None);
+    }
+
+    #[test]
+    fn place_near_takes_the_closest_free_tile() {
+        let mut grid = Grid::new(5, 3);
+        let center = Position::new(2, 1);
+        grid.place(Occupant::Trooper(0), center);
+        let spot = grid.place_near(Occupant::Bug(0), center).unwrap();
+        assert_eq!(spot.tiles_to(center), 1);
+        assert_eq!(grid.occupant_at(spot), Some(Occupant::Bug(0)));
+
+        let mut packed = Grid::new(1, 1);
+        packed.place(Occupant::Trooper(0), Position::new(0, 0));
+        assert_eq!(packed.place_near(Occupant::Bug(0), Position::new(0, 0)), None);
+    }
+
+    fn alert_at(level: f32) -> HiveAlert {
+        HiveAlert { level, noise: 0.0 }
+    }
+
+    #[test]
+    fn alert_tiers_split_at_quarters() {
+        assert_eq!(alert_at(24.9).tier(), AlertTier::Calm);
+        assert_eq!(alert_at(25.0).tier(), AlertTier::Wary);
+        assert_eq!(alert_at(50.0).tier(), AlertTier::Alerted);
+        assert_eq!(alert_at(75.0).tier(), AlertTier::Frenzied);
+        assert_eq!(alert_at(100.0).wave_bonus(), 3);
+    }
+
+    #[test]
+    fn noise_raises_the_alert_and_reports_the_new_tier() {
+        let mut alert = HiveAlert::new();
+        alert.make_noise(250.0);
+        assert_eq!(alert.settle_round(), Some(AlertTier::Wary));
+        assert_eq!(alert.level(), 25.5);
+        alert.make_noise(5.0);
+        assert_eq!(alert.settle_round(), None);
+    }
+
+    #[test]
+    fn quiet_rounds_calm_the_hive_down() {
+        let mut alert = alert_at(26.0);
+        assert_eq!(alert.settle_round(), Some(AlertTier::Calm));
+        assert_eq!(alert.level(), 23.5);
+        for _ in 0..10 { alert.settle_round(); }
+        assert_eq!(alert.level(), 0.0);
     }
 }
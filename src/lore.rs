@@ -0,0 +1,65 @@
+#![allow(dead_code)]
+// Imports
+
+use crate::log;
+use crate::bugs::{Bug, FamilySynergy, FAMILY_SYNERGIES};
+
+// Bestiary
+
+/// A species the squad has scanned or killed.
+#[derive(Debug, Clone)]
+pub struct BestiaryEntry {
+    pub name: &'static str,
+    pub family: &'static str,
+    pub encounters: u32,
+}
+
+/// Keeps what the squad has learned about the hive: species met and family synergies seen in action.
+#[derive(Default)]
+pub struct LoreMaster {
+    species: Vec<BestiaryEntry>,
+    synergies: Vec<&'static str>,
+}
+
+impl LoreMaster {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn species(&self) -> &[BestiaryEntry] { &self.species }
+
+    pub fn knows_synergy(&self, name: &str) -> bool { self.synergies.contains(&name) }
+
+    /// Writes the bug up, opening a new entry the first time its species turns up.
+    pub fn record_bug(&mut self, bug: &Bug) {
+        if let Some(entry) = self.species.iter_mut().find(|e| e.name == bug.name()) {
+            entry.encounters += 1;
+            return;
+        }
+        self.species.push(BestiaryEntry { name: bug.name(), family: bug.family(), encounters: 1 });
+        log!(info, format!("📖 New bestiary entry: {} ({})", bug.name(), bug.family()), false);
+    }
+
+    /// Notes a synergy the first time the squad sees it at work.
+    pub fn record_synergy(&mut self, synergy: &'static FamilySynergy) {
+        if self.knows_synergy(synergy.name) { return; }
+        self.synergies.push(synergy.name);
+        log!(info, format!("📖 Bestiary: {} - {}. {}", synergy.family, synergy.name, synergy.description), false);
+    }
+
+    /// Every family the squad has met, its known species, and its synergy if it's been seen.
+    pub fn log_bestiary(&self) {
+        log!(info, "📖 ===== BESTIARY =====", false);
+        for synergy in FAMILY_SYNERGIES {
+            let members: Vec<String> = self.species.iter()
+                .filter(|e| e.family == synergy.family)
+                .map(|e| format!("{} x{}", e.name, e.encounters))
+                .collect();
+            if members.is_empty() { continue; }
+            log!(info, format!("{}: {}", synergy.family, members.join(", ")), false);
+            if self.knows_synergy(synergy.name) {
+                log!(info, format!("  {}: {}", synergy.name, synergy.description), false);
+            } else {
+                log!(info, "  Synergy: ???", false);
+            }
+        }
+    }
+}
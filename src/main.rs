@@ -8,6 +8,7 @@ mod battlefield;
 mod combat;
 mod troopers;
 mod armory;
+mod lore;
 mod tui;
 
 use battlefield::Overwatch;
@@ -1,6 +1,5 @@
 #![allow(dead_code)]
 // TODO: Create Loadout handling for trooper, possibly being able to pass in Commander?
-// TODO: Add way to apply effects to a Trooper's stats'
 // TODO: Create way to take damage and way to attack
 
 // ============ Imports =================
@@ -10,8 +9,9 @@ use std::default::Default;
 
 use crate::{boost, log};
 use crate::utils::{SafeSub,RandBools as Bools};
-use crate::armory::{Armory, Loadout};
-use crate::bugs::{Broodmother, Bug};
+use crate::armory::{ActionCost, DamageType, Distance, ActiveEffect, Armory, Effect, EquipmentFlaw, Gear, GearActivation, GearUseError, Loadout, StatModifier};
+use crate::bugs::Bug;
+use crate::combat::{DamageEvent, DamageProfile, DamageSource, Joker, Mitigated, RangeMods};
 
 // ============ Classes =================
 
@@ -50,10 +50,32 @@ struct TrooperFlaws {
         glass_jaw: bool,
 }
 
+/// Where a trooper sits between fighting fit and gone for good.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Vitals {
+    #[default]
+    Up,
+    Downed { turns_left: u8, stabilized: bool },
+    Dead,
+}
+
+const BLEED_OUT_TURNS: u8 = 3;
+const TRIAGE_REVIVE_RATIO: f32 = 0.25;
+
+const BASE_THREAT: f32 = 10.0;
+const MAX_THREAT: f32 = 200.0;
+// Accumulated threat fades by this much every turn
+const THREAT_DECAY: f32 = 0.8;
+
+/// Sickness worsens a stage every chamber it goes untreated, up to this.
+const MAX_SICKNESS: u8 = 3;
+
 #[derive(Default, Debug, Copy, Clone)]
 struct TrooperStats {
     hp: u32,
+    max_hp: u32,
     ap: u32,
+    max_ap: u32,
     dmg_mod: f32,
     accuracy: f32,
     agility: f32,
@@ -63,7 +85,9 @@ impl TrooperStats {
     fn new (hp: u32, ap: u32, dmg_mod: f32, accuracy: f32, agility: f32) -> TrooperStats {
         TrooperStats {
             hp,
+            max_hp: hp,
             ap,
+            max_ap: ap,
             dmg_mod,
             accuracy,
             agility,
@@ -71,6 +95,19 @@ impl TrooperStats {
     }
 }
 
+/// What a trooper still has left to spend this turn: one Action ('A') and one Free action ('F').
+#[derive(Debug, Copy, Clone)]
+struct TurnActions {
+    action: bool,
+    free: bool,
+}
+
+impl Default for TurnActions {
+    fn default() -> Self {
+        TurnActions { action: true, free: true }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Trooper {
     pub class: TrooperClass,
@@ -78,7 +115,15 @@ pub struct Trooper {
     perk: ClassPerk,
     r#trait: TrooperTraits,
     flaw: TrooperFlaws,
+    innate: TrooperStats,
+    gear_mods: StatModifier,
     stats: TrooperStats,
+    vitals: Vitals,
+    actions: TurnActions,
+    effects: Vec<ActiveEffect>,
+    threat: f32,
+    /// Stage of whatever they caught scavenging; 0 is healthy.
+    sickness: u8,
 }
 
 impl Trooper {
@@ -95,7 +140,14 @@ impl Trooper {
             perk,
             r#trait,
             flaw,
+            innate: stats,
+            gear_mods: StatModifier::default(),
             stats,
+            vitals: Vitals::Up,
+            actions: TurnActions::default(),
+            effects: Vec::new(),
+            threat: 0.0,
+            sickness: 0,
         }
     }
 
@@ -187,6 +239,8 @@ impl Trooper {
 
         stats.hp = stats.hp.clamp(10, 200);
         stats.ap = stats.ap.clamp(0, 100);
+        stats.max_hp = stats.hp;
+        stats.max_ap = stats.ap;
         stats.dmg_mod = stats.dmg_mod.clamp(0.5, 2.0);
         stats.accuracy = stats.accuracy.clamp(0.5, 1.5);
         stats.agility = stats.agility.clamp(0.0, 1.0);
@@ -204,52 +258,527 @@ impl Trooper {
         Self::apply_modifiers(&mut base, traits, flaws)
     }
 
-    fn damage_mod(&self, base_dmg: u32)  -> u32 {
-        let dmg_mod = self.stats.dmg_mod;
+    fn apply_stat_modifier(stats: &mut TrooperStats, m: StatModifier, sign: i32) {
+        let (hp, ap) = (m.hp * sign, m.ap * sign);
+        boost!(stats, hp > 0, max_hp += hp as u32);
+        boost!(stats, hp > 0, hp += hp as u32);
+        boost!(stats, hp < 0, max_hp -= hp.unsigned_abs());
+        boost!(stats, hp < 0, hp -= hp.unsigned_abs());
+        boost!(stats, ap > 0, max_ap += ap as u32);
+        boost!(stats, ap > 0, ap += ap as u32);
+        boost!(stats, ap < 0, max_ap -= ap.unsigned_abs());
+        boost!(stats, ap < 0, ap -= ap.unsigned_abs());
+        stats.hp = stats.hp.min(stats.max_hp);
+        stats.ap = stats.ap.min(stats.max_ap);
+        stats.dmg_mod += m.dmg_mod * sign as f32;
+        stats.accuracy += m.accuracy * sign as f32;
+        stats.agility = (stats.agility + m.agility * sign as f32).clamp(0.0, 1.0);
+    }
 
-        let mod_dmg = (base_dmg as f32 * dmg_mod) as u32;
+    /// Puts on every intact wearable. Items flagged OnlyOneActivePerSquad are skipped if a squadmate
+    /// already claimed one of the same name.
+    pub fn equip_passives(&mut self, claimed: &mut Vec<&'static str>) {
+        let mut mods = StatModifier::default();
+        let mut lingering = Vec::new();
 
-        mod_dmg
+        for item in self.loadout.gear().iter().filter(|g| g.is_passive() && !g.is_destroyed()) {
+            if matches!(item.flaw(), Some(EquipmentFlaw::OnlyOneActivePerSquad)) {
+                if claimed.contains(&item.name()) { continue; }
+                claimed.push(item.name());
+            }
+            mods = mods + item.passive_modifier();
+            lingering.extend(item.passive_effect().map(|e| ActiveEffect::worn(e, item.name())));
+        }
+
+        Self::apply_stat_modifier(&mut self.stats, mods, 1);
+        self.gear_mods = mods;
+        self.effects.extend(lingering);
     }
 
+    /// Destroys a gear slot and strips whatever it was granting.
+    pub fn destroy_gear(&mut self, slot: usize) {
+        let name = self.loadout.gear()[slot].name();
+        if let Some(lost) = self.loadout.destroy_gear(slot) {
+            Self::apply_stat_modifier(&mut self.stats, lost, -1);
+            self.gear_mods = self.gear_mods + -lost;
+            if let Some(i) = self.effects.iter().position(|e| e.worn == Some(name)) {
+                self.effects.remove(i);
+            }
+            log!(info, format!("{:?}'s {} is destroyed!", self.class, name), false);
+        }
+    }
+
+    /// Armor worn down to nothing can take the plating with it. Fragile armor always goes.
+    fn check_armor_break(&mut self) {
+        if self.stats.ap > 0 { return; }
+        let Some(slot) = self.loadout.gear().iter().position(|g| !g.is_destroyed() && g.grants_armor()) else { return; };
+        if self.flaw.fragile_armor || Bools::rand_bool(0.25) {
+            self.destroy_gear(slot);
+        }
+    }
+
+    /// Class base, what trait/flaw changed, what gear adds, and the current result.
+    pub fn stat_breakdown(&self) -> String {
+        let base = Self::get_base_stats(self.class);
+        let innate = self.innate;
+        let gear = self.gear_mods;
+        let cur = self.stats;
+        format!(
+            "HP {}/{} (base {}, trait/flaw {:+}, gear {:+}) | AP {}/{} (base {}, trait/flaw {:+}, gear {:+}) | \
+             DMG x{:.2} (base {:.2}, trait/flaw {:+.2}, gear {:+.2}) | ACC {:.2} (base {:.2}, trait/flaw {:+.2}, gear {:+.2}) | \
+             AGI {:.2} (base {:.2}, trait/flaw {:+.2}, gear {:+.2})",
+            cur.hp, cur.max_hp, base.hp, innate.hp as i32 - base.hp as i32, gear.hp,
+            cur.ap, cur.max_ap, base.ap, innate.ap as i32 - base.ap as i32, gear.ap,
+            cur.dmg_mod, base.dmg_mod, innate.dmg_mod - base.dmg_mod, gear.dmg_mod,
+            cur.accuracy, base.accuracy, innate.accuracy - base.accuracy, gear.accuracy,
+            cur.agility, base.agility, innate.agility - base.agility, gear.agility,
+        )
+    }
+
+    pub fn dmg_mod(&self) -> f32 { self.stats.dmg_mod }
+
     pub fn hp(&self) -> u32 { self.stats.hp }
 
     pub fn ap(&self) -> u32 { self.stats.ap }
 
-    pub fn damage(&self) -> (u32, u32, u32) {
-        let weapon = self.loadout.equipped_weapon();
-        let (dmg, hp_dmg, ap_dmg) = weapon.damage();
-        let mod_dmg = self.damage_mod(dmg);
-        (
-            mod_dmg,
-            hp_dmg,
-            ap_dmg,
-        )
+    pub fn max_hp(&self) -> u32 { self.stats.max_hp }
+
+    pub fn max_ap(&self) -> u32 { self.stats.max_ap }
+
+    pub fn hp_ratio(&self) -> f32 { self.stats.hp as f32 / self.stats.max_hp.max(1) as f32 }
+
+    pub fn armor_damaged(&self) -> bool { self.stats.ap < self.stats.max_ap }
+
+    /// Whether a restorative effect would be wasted on this trooper.
+    pub fn at_max_for(&self, effect: &Effect) -> bool {
+        match effect {
+            Effect::BuffAP { .. } => !self.armor_damaged(),
+            Effect::Heal { .. } | Effect::Regen { .. } => self.stats.hp >= self.stats.max_hp,
+            _ => false,
+        }
+    }
+
+    /// Unscaled weapon profile; dmg_mod is applied by the Joker alongside the hit outcome.
+    /// Armor Shred and an active IgnoreArmor/ArmorPierce stack on the weapon's own pierce.
+    pub fn damage_profile(&self) -> DamageProfile {
+        let mut profile = self.loadout.equipped_weapon().damage_profile();
+        let perk = if self.perk == ClassPerk::ArmorShred { 25 } else { 0 };
+        let active = self.effects.iter().map(|a| a.effect.pierce()).max().unwrap_or(0);
+        profile.pierce = profile.pierce.saturating_add(perk).saturating_add(active).min(100);
+        profile
+    }
+
+    pub fn damage_source(&self) -> DamageSource {
+        DamageSource::Weapon(self.loadout.equipped_weapon().name())
     }
 
     pub fn accuracy(&self) -> f32 {
         let acc = self.stats.accuracy;
         let equipped_weapon = self.loadout.equipped_weapon();
-        let weapon_acc_del = equipped_weapon.accuracy();
+        let weapon_acc_del = equipped_weapon.accuracy() + ActiveEffect::accuracy_delta(&self.effects);
         let mult = (1.0 + weapon_acc_del).max(0.5);
-        acc * mult
+        acc * mult * (1.0 - 0.08 * self.sickness as f32)
+    }
+
+    pub fn agility(&self) -> f32 { (self.stats.agility - 0.05 * self.sickness as f32).max(0.0) }
+
+    /// Tiles per move action: 2 plus up to 3 from agility, then BuffMove. Negative BuffMove halves it.
+    pub fn move_range(&self) -> u32 {
+        let base = 2 + (self.stats.agility * 3.0).round() as i32;
+        let tiles = self.effects.iter().fold(base, |acc, a| match a.effect {
+            Effect::BuffMove { mv, .. } if mv < 0 => acc / 2,
+            Effect::BuffMove { mv, .. } => acc + mv as i32,
+            _ => acc,
+        });
+        tiles.max(1) as u32
+    }
+
+    pub fn weapon_range(&self) -> Distance { self.loadout.equipped_weapon().range() }
+
+    pub fn in_range(&self, tiles: u32) -> bool { self.loadout.equipped_weapon().in_range(tiles) }
+
+    pub fn burns_cover(&self) -> bool { self.loadout.equipped_weapon().burns_cover() }
+
+    /// Noise this trooper's shot makes; silenced troopers only make a quarter of it.
+    pub fn weapon_noise(&self) -> f32 {
+        let noise = self.loadout.equipped_weapon().noise();
+        if self.has_effect(|e| matches!(e, Effect::AlwaysSilent)) { noise * 0.25 } else { noise }
+    }
+
+    pub fn is_loudmouth(&self) -> bool { self.flaw.loudmouth }
+
+    pub fn has_fast_reflexes(&self) -> bool { self.r#trait.fast_reflexes }
+
+    /// Rubble shifted per turn of digging: Engineers know where to dig, the right tools cut straight through.
+    pub fn dig_power(&self) -> u32 {
+        let class = if self.class == TrooperClass::Engineer { 1 } else { 0 };
+        let tools = if self.loadout.gear().iter().any(|g| g.is_digging_tool()) { 2 } else { 0 };
+        1 + class + tools
+    }
+
+    pub fn is_explosive(&self) -> bool { self.loadout.equipped_weapon().has_damage_type(DamageType::Explosive) }
+
+    pub fn range_mods(&self, tiles: u32) -> RangeMods { self.loadout.equipped_weapon().range_mods(tiles) }
+
+    /// Not dead yet. Downed troopers still count.
+    pub fn is_alive(&self) -> bool { self.vitals != Vitals::Dead }
+
+    pub fn is_up(&self) -> bool { self.vitals == Vitals::Up }
+
+    pub fn is_downed(&self) -> bool { matches!(self.vitals, Vitals::Downed { .. }) }
+
+    pub fn is_stabilized(&self) -> bool { matches!(self.vitals, Vitals::Downed { stabilized: true, .. }) }
+
+    pub fn vitals(&self) -> Vitals { self.vitals }
+
+    fn bleed_out_turns(&self) -> u8 {
+        let mut turns = BLEED_OUT_TURNS;
+        if self.r#trait.hardy { turns += 1; }
+        if self.flaw.glass_jaw { turns -= 1; }
+        turns
+    }
+
+    fn check_down(&mut self) {
+        if self.is_up() && self.stats.hp == 0 {
+            self.vitals = Vitals::Downed { turns_left: self.bleed_out_turns(), stabilized: false };
+            log!(info, format!("{:?} is down and bleeding out!", self.class), false);
+        }
+    }
+
+    fn die(&mut self) {
+        self.vitals = Vitals::Dead;
+        self.stats.hp = 0;
+        self.effects.clear();
+        log!(info, format!("{:?} is dead.", self.class), false);
+    }
+
+    /// Counts a downed trooper's bleed-out timer down. Stabilized troopers hold steady.
+    pub fn bleed_out(&mut self) {
+        if let Vitals::Downed { turns_left, stabilized: false } = self.vitals {
+            if turns_left <= 1 {
+                self.die();
+            } else {
+                self.vitals = Vitals::Downed { turns_left: turns_left - 1, stabilized: false };
+            }
+        }
+    }
+
+    /// Face-down in water: stabilizing doesn't help, and it goes twice as fast as bleeding out.
+    pub fn drown(&mut self) {
+        if let Vitals::Downed { turns_left, .. } = self.vitals {
+            self.vitals = Vitals::Downed { turns_left, stabilized: false };
+            self.bleed_out();
+            self.bleed_out();
+        }
+    }
+
+    pub fn stabilize(&mut self) -> bool {
+        match self.vitals {
+            Vitals::Downed { turns_left, stabilized: false } => {
+                self.vitals = Vitals::Downed { turns_left, stabilized: true };
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Gets a downed trooper back on their feet. The dead stay dead.
+    pub fn revive(&mut self, hp: u32) -> bool {
+        if !self.is_downed() { return false; }
+        self.vitals = Vitals::Up;
+        self.stats.hp = self.recovered(hp).clamp(1, self.stats.max_hp);
+        true
+    }
+
+    /// Medics with Combat Triage can pull a downed ally up instead of just stabilizing them.
+    pub fn can_triage(&self) -> bool { self.perk == ClassPerk::CombatTriage }
+
+    pub fn can_scan(&self) -> bool { self.perk == ClassPerk::BugScan }
+
+    pub fn triage_hp(&self, patient: &Trooper) -> u32 {
+        ((patient.stats.max_hp as f32) * TRIAGE_REVIVE_RATIO * self.healing_output()) as u32
+    }
+
+    pub fn attack(&self, target: &mut Bug) -> Mitigated {
+        let profile = self.damage_profile().scaled(self.stats.dmg_mod);
+        target.take_hit(&DamageEvent::new(profile, self.damage_source()))
+    }
+
+    /// Runs a hit through armor. Any hit on a downed trooper finishes them.
+    pub fn take_hit(&mut self, event: &DamageEvent) -> Mitigated {
+        if self.is_downed() {
+            if !event.profile.is_zero() { self.die(); }
+            return Mitigated::default();
+        }
+        let result = Joker::mitigate(&event.profile, self.stats.hp, self.stats.ap);
+        self.stats.ap = self.stats.ap.safe_sub(result.ap_loss);
+        self.stats.hp = self.stats.hp.safe_sub(result.hp_loss);
+        self.check_armor_break();
+        self.check_down();
+        result
+    }
+
+    pub fn lose_hp(&mut self, dmg: u32) {
+        self.stats.hp = self.stats.hp.safe_sub(dmg);
+        self.check_down();
+    }
+
+    /// Restores HP up to max, returning how much actually landed. Slow recovery blunts it.
+    pub fn heal(&mut self, amount: u32) -> u32 {
+        if !self.is_up() { return 0; }
+        let amount = self.recovered(amount);
+        let before = self.stats.hp;
+        self.stats.hp = (before + amount).min(self.stats.max_hp);
+        self.stats.hp - before
+    }
+
+    /// Slow healers only get part of any HP put back into them.
+    fn recovered(&self, amount: u32) -> u32 {
+        if self.flaw.slow_recovery { (amount as f32 * 0.6) as u32 } else { amount }
+    }
+
+    /// Restores AP up to max, returning how much actually landed.
+    pub fn repair_armor(&mut self, amount: u32) -> u32 {
+        let before = self.stats.ap;
+        self.stats.ap = (before + amount).min(self.stats.max_ap).max(before);
+        self.stats.ap - before
+    }
+
+    /// Multiplier on healing this trooper hands out.
+    pub fn healing_output(&self) -> f32 {
+        if self.r#trait.battle_medic { 1.25 } else { 1.0 }
+    }
+
+    /// Multiplier on armor this trooper repairs.
+    pub fn repair_output(&self) -> f32 {
+        if self.r#trait.mechanic { 1.5 } else { 1.0 }
+    }
+
+    // ---- Turn economy ----
+
+    pub fn begin_turn(&mut self) {
+        self.actions = TurnActions::default();
+        self.loadout.tick_gear();
+        self.threat *= THREAT_DECAY;
+    }
+
+    // ---- Threat ----
+
+    pub fn add_threat(&mut self, amount: f32) {
+        self.threat = (self.threat + amount).clamp(0.0, MAX_THREAT);
+    }
+
+    pub fn reset_threat(&mut self) { self.threat = 0.0; }
+
+    /// How much the bugs want this trooper dead. Cloaked troopers don't add any.
+    pub fn threat(&self) -> f32 {
+        if self.is_cloaked() { return 0.0; }
+        let mut threat = BASE_THREAT + self.threat;
+        if self.flaw.loudmouth { threat += 15.0; }
+        if self.perk == ClassPerk::EchoProtocol { threat = threat * 1.5 + 20.0; }
+        if self.has_effect(|e| matches!(e, Effect::AggroPull { .. })) { threat += 100.0; }
+        if self.has_effect(|e| matches!(e, Effect::AlwaysSilent)) { threat *= 0.25; }
+        threat
+    }
+
+    fn can_afford(&self, cost: ActionCost) -> bool {
+        match cost {
+            ActionCost::Free => self.actions.free || self.actions.action,
+            ActionCost::Action => self.actions.action,
+        }
+    }
+
+    /// Free actions fall back to spending the main Action once the Free one is gone.
+    fn spend(&mut self, cost: ActionCost) -> bool {
+        match cost {
+            ActionCost::Free if self.actions.free => { self.actions.free = false; true },
+            _ if self.actions.action => { self.actions.action = false; true },
+            _ => false,
+        }
+    }
+
+    pub fn has_action(&self) -> bool { self.actions.action }
+
+    pub fn spend_attack(&mut self) -> bool { self.spend(ActionCost::Action) }
+
+    /// Moving uses the Free action first, then eats the main Action.
+    pub fn spend_move(&mut self) -> bool { self.spend(ActionCost::Free) }
+
+    // ---- Gear ----
+
+    pub fn equipped_gear(&self) -> &Gear { self.loadout.equipped_gear() }
+
+    pub fn cycle_gear(&mut self) {
+        let next = (self.loadout.equipped_gear_slot() + 1) % self.loadout.gear_count().max(1);
+        self.loadout.swap_gear(next);
     }
 
-    pub fn agility(&self) -> f32 { self.stats.agility }
+    /// Uses the first sensor or tripwire that's ready to go, leaving the equipped slot as it was.
+    pub fn use_warning_gear(&mut self) -> Option<GearActivation> {
+        let slot = self.loadout.gear().iter().position(|g| g.warns_of_ambush())?;
+        let equipped = self.loadout.equipped_gear_slot();
+        self.loadout.swap_gear(slot);
+        let activation = self.use_gear().ok();
+        self.loadout.swap_gear(equipped);
+        activation
+    }
 
-    pub fn is_alive(&self) -> bool { self.hp() > 0 }
+    /// Fraction of limited-use gear uses this trooper has burned through.
+    pub fn supplies_spent(&self) -> f32 {
+        let missing: u32 = self.loadout.gear().iter().map(|g| g.uses_missing()).sum();
+        let left: u32 = self.loadout.gear().iter().filter_map(|g| g.uses_left()).sum();
+        missing as f32 / (missing + left).max(1) as f32
+    }
 
-    pub fn attack(&self, target: &mut Bug) {
-        let (dmg, hp_dmg, ap_dmg) = self.damage();
-        target.take_damage(dmg, hp_dmg, ap_dmg);
+    /// Charges left in intact limited-use gear.
+    pub fn spare_charges(&self) -> u32 {
+        self.loadout.gear().iter().filter(|g| !g.is_destroyed()).filter_map(|g| g.uses_left()).sum()
     }
 
-    pub fn take_damage(&mut self, _dmg: u32, hp_dmg: u32, ap_dmg: u32) {
+    /// Loads `uses` worth of charges into the emptiest gear first. Returns how many fit.
+    pub fn resupply(&mut self, uses: u32) -> u32 {
+        let mut left = uses;
+        while left > 0 {
+            let Some(item) = self.loadout.gear_mut().iter_mut()
+                .filter(|g| g.uses_missing() > 0)
+                .max_by_key(|g| g.uses_missing()) else { break; };
+            left -= item.restock(1);
+        }
+        uses - left
+    }
+
+    /// Gives up to `uses` charges for a squadmate, fullest gear first. Returns how many it gave.
+    pub fn hand_over(&mut self, uses: u32) -> u32 {
+        let mut left = uses;
+        while left > 0 {
+            let Some(item) = self.loadout.gear_mut().iter_mut()
+                .filter(|g| !g.is_destroyed() && g.uses_left().is_some_and(|n| n > 0))
+                .max_by_key(|g| g.uses_left()) else { break; };
+            left -= item.draw(1);
+        }
+        uses - left
+    }
+
+    /// Racks the hardest-hitting weapon and the first piece of gear that's actually ready to use.
+    pub fn reorganize(&mut self) {
+        let best = self.loadout.weapons().iter()
+            .enumerate()
+            .max_by_key(|(_, w)| { let p = w.damage_profile(); p.hp + p.ap })
+            .map_or(0, |(i, _)| i);
+        self.loadout.swap_weapon(best);
+        if let Some(slot) = self.loadout.gear().iter().position(|g| g.can_use().is_ok()) {
+            self.loadout.swap_gear(slot);
+        }
+    }
+
+    pub fn can_use_gear(&self) -> Result<(), GearUseError> {
+        let gear = self.loadout.equipped_gear();
+        gear.can_use()?;
+        match gear.action_cost() {
+            Some(cost) if !self.can_afford(cost) => Err(GearUseError::NoActionLeft),
+            _ => Ok(()),
+        }
+    }
+
+    /// Uses the equipped gear, paying its action cost. The effect itself is resolved by Overwatch.
+    pub fn use_gear(&mut self) -> Result<GearActivation, GearUseError> {
+        self.can_use_gear()?;
+        let activation = self.loadout.use_equipped_gear()?;
+        if let Some(cost) = activation.cost {
+            self.spend(cost);
+        }
+        Ok(activation)
+    }
+
+    // ---- Effects ----
+
+    pub fn apply_effect(&mut self, mut effect: Effect) {
+        match effect {
+            Effect::Heal { hp, .. } => { self.heal(hp); },
+            Effect::Revive { hp } => { self.revive(hp); },
+            Effect::Regen { .. } if self.is_downed() => { self.stabilize(); },
+            Effect::BuffAP { ap, turns: 0, .. } if ap >= 0 => { self.repair_armor(ap as u32); },
+            // Remember the swing that actually landed, so expiry undoes exactly that much
+            Effect::BuffAP { ap, turns, area } => {
+                let before = self.stats.ap;
+                let cap = self.stats.max_ap.max(before);
+                self.stats.ap = (before as i64 + ap as i64).clamp(0, cap as i64) as u32;
+                effect = Effect::BuffAP { ap: self.stats.ap as i32 - before as i32, turns, area };
+            },
+            Effect::CleanseDebuffs { .. } => {
+                self.effects.retain(|e| !e.is_debuff());
+                self.cure_sickness();
+            },
+            Effect::ActionRefill => self.actions.action = true,
+            _ => {},
+        }
+        if let Some(active) = ActiveEffect::new(effect) {
+            self.effects.push(active);
+        }
+    }
+
+    pub fn tick_effects(&mut self) {
+        if !self.is_alive() { return; }
+        let tick = ActiveEffect::tick_all(&mut self.effects);
+        // Regen on a stabilized trooper is what gets them back up (Stabilizer Drone)
+        if self.is_stabilized() && tick.heal > 0 {
+            self.revive(tick.heal);
+        } else {
+            self.heal(tick.heal);
+        }
         let stats = &mut self.stats;
-        boost!(stats, stats.ap != 0, ap -= ap_dmg);
-        boost!(stats, stats.ap == 0, hp -= hp_dmg);
-        // boost!(stats, true, hp -= dmg);
-        // boost!(stats, true, ap -= dmg);
+        boost!(stats, tick.hp_dmg > 0, hp -= tick.hp_dmg);
+        boost!(stats, tick.ap_dmg > 0, ap -= tick.ap_dmg);
+        boost!(stats, tick.expired_ap > 0, ap -= tick.expired_ap as u32);
+        boost!(stats, tick.expired_ap < 0, ap = (stats.ap + tick.expired_ap.unsigned_abs()).min(stats.max_ap));
+        self.check_down();
+    }
+
+    pub fn has_effect(&self, pred: impl Fn(&Effect) -> bool) -> bool {
+        self.effects.iter().any(|e| pred(&e.effect))
+    }
+
+    pub fn has_debuff(&self) -> bool {
+        self.is_sick() || self.effects.iter().any(|e| e.is_debuff())
+    }
+
+    // ---- Sickness ----
+
+    pub fn is_sick(&self) -> bool { self.sickness > 0 }
+
+    pub fn sickness(&self) -> u8 { self.sickness }
+
+    /// Odds that a meal from the larder turns. Acid-phobes gag on the resin and wolf down the worst of it.
+    pub fn infection_risk(&self, base: f32) -> f32 {
+        if self.flaw.acid_phobia { (base * 2.0).min(1.0) } else { base }
+    }
+
+    pub fn infect(&mut self) { self.sickness = self.sickness.max(1); }
+
+    pub fn cure_sickness(&mut self) { self.sickness = 0; }
+
+    /// Another chamber without treatment. Late stages start eating at their health too.
+    pub fn worsen_sickness(&mut self) {
+        if !self.is_sick() || !self.is_alive() { return; }
+        self.sickness = (self.sickness + 1).min(MAX_SICKNESS);
+        if self.sickness == MAX_SICKNESS {
+            self.stats.hp = self.stats.hp.safe_sub(self.stats.max_hp / 10).max(1);
+        }
+    }
+
+    pub fn can_act(&self) -> bool {
+        self.is_up() && !self.has_effect(|e| matches!(e, Effect::Stun { .. }))
+    }
+
+    pub fn is_cloaked(&self) -> bool {
+        self.has_effect(|e| matches!(e, Effect::Cloak { .. }))
+    }
+
+    /// Attacking drops a cloak from gear that can't hold up to it. Returns whether one dropped.
+    pub fn break_cloak(&mut self) -> bool {
+        if !self.is_cloaked() || !self.loadout.gear().iter().any(|g| g.breaks_on_attack()) { return false; }
+        self.effects.retain(|e| !matches!(e.effect, Effect::Cloak { .. }));
+        true
     }
 }
 
@@ -259,13 +788,17 @@ pub struct Commander {
 
 impl Commander {
     pub fn new(count: usize) -> Self {
-        let team = Self::test_trooper_creation(count);
+        let mut team = Self::test_trooper_creation(count);
+        let mut claimed = Vec::new();
+        for trooper in team.iter_mut() {
+            trooper.equip_passives(&mut claimed);
+        }
         Commander { team }
     }
 
     pub fn test_trooper_creation(count: usize) -> Vec<Trooper> {
         use TrooperClass::*;
-        let class_pool = vec![Heavy, Scout, Engineer, Medic, ExoTech, Handler, Decoy];
+        let class_pool = [Heavy, Scout, Engineer, Medic, ExoTech, Handler, Decoy];
         let mut rng = rand::rng();
 
         (0..count)
@@ -277,13 +810,13 @@ impl Commander {
     }
 
     pub fn spawn_troopers(&self, team: &[Trooper]) {
-        for (i, trooper) in team.into_iter().enumerate() {
+        for (i, trooper) in team.iter().enumerate() {
             log!(info, format!(" ======= Trooper {} ======== ", i + 1), false);
             log!(info, format!("Class: {:?}", trooper.class), false);
             log!(info, format!("Perk: {:?}", trooper.perk), false);
             log!(info, format!("Trait: {:?}", trooper.r#trait), false);
             log!(info, format!("Flaw: {:?}", trooper.flaw), false);
-            log!(info, format!("Stats: {:?}", trooper.stats), true);
+            log!(info, format!("Stats: {}", trooper.stat_breakdown()), true);
         }
     }
 
@@ -293,8 +826,8 @@ impl Commander {
         Armory::log_loadout(&trooper.loadout);
     }
 
-    pub fn log_team_gear(&self, team: &Vec<Trooper>) {
-        for (i, trooper) in team.into_iter().enumerate() {
+    pub fn log_team_gear(&self, team: &[Trooper]) {
+        for (i, trooper) in team.iter().enumerate() {
             Self::log_trooper_gear(i, trooper);
         }
     }
@@ -303,19 +836,245 @@ impl Commander {
         trooper.attack(target);
     }
 
-    pub fn trooper_attacked(&self, trooper: &mut Trooper, dmg: u32, hp_dmg: u32, ap_dmg: u32) {
-        trooper.take_damage(dmg, hp_dmg, ap_dmg);
+    pub fn trooper_attacked(&self, trooper: &mut Trooper, event: &DamageEvent) -> Mitigated {
+        trooper.take_hit(event)
     }
 
-    pub fn apply_damage_to_trooper(&mut self, idx: usize, dmg: u32, hp_dmg: u32, ap_dmg: u32) {
-        let target = &mut self.team[idx];
-        target.take_damage(dmg, hp_dmg, ap_dmg);
+    pub fn apply_damage_to_trooper(&mut self, idx: usize, event: &DamageEvent) -> Mitigated {
+        self.team[idx].take_hit(event)
+    }
+
+    /// Every fight starts with a clean slate of aggro.
+    pub fn reset_threat(&mut self) {
+        for t in self.team.iter_mut() { t.reset_threat(); }
+    }
+
+    /// After a fight, anyone still bleeding gets patched up enough to walk.
+    pub fn recover_downed(&mut self) {
+        for t in self.team.iter_mut().filter(|t| t.is_downed()) {
+            let hp = (t.stats.max_hp / 10).max(1);
+            t.revive(hp);
+            log!(info, format!("{:?} is hauled back to their feet with {} HP", t.class, hp), false);
+        }
+    }
+
+    /// Moves up to `uses` charges to Trooper `ti`, one at a time, from whichever squadmate has spent
+    /// the least of their own. Returns how many each donor gave.
+    pub fn resupply(&mut self, ti: usize, uses: u32) -> Vec<(usize, u32)> {
+        let mut given: Vec<(usize, u32)> = Vec::new();
+        for _ in 0..uses {
+            let need = self.team[ti].supplies_spent();
+            let Some(di) = (0..self.team.len())
+                .filter(|&di| di != ti && self.team[di].is_up() && self.team[di].spare_charges() > 0)
+                .filter(|&di| self.team[di].supplies_spent() < need)
+                .min_by(|&a, &b| self.team[a].supplies_spent().total_cmp(&self.team[b].supplies_spent())) else { break; };
+            if self.team[ti].resupply(1) == 0 { break; }
+            self.team[di].hand_over(1);
+            match given.iter_mut().find(|(d, _)| *d == di) {
+                Some((_, n)) => *n += 1,
+                None => given.push((di, 1)),
+            }
+        }
+        given
+    }
+
+    /// The squad pools its charges: whoever has burned through the most gets first pick of the spares.
+    pub fn share_supplies(&mut self, uses: u32) {
+        let mut order: Vec<usize> = (0..self.team.len()).filter(|&ti| self.team[ti].is_up()).collect();
+        order.sort_by(|&a, &b| self.team[b].supplies_spent().total_cmp(&self.team[a].supplies_spent()));
+        let mut shared = false;
+        for ti in order {
+            for (di, n) in self.resupply(ti, uses) {
+                shared = true;
+                log!(info, format!("Trooper#{} takes {n} spare charges from Trooper#{}", ti + 1, di + 1), false);
+            }
+        }
+        if !shared {
+            log!(info, "Nobody has spare charges to pass around", false);
+        }
+    }
+
+    /// Between chambers: each Medic treats one sick squadmate, and everyone else's sickness gets worse.
+    pub fn treat_sickness(&mut self) {
+        let medics = self.team.iter().filter(|t| t.is_up() && t.can_triage()).count();
+        let mut treated = 0;
+        for (ti, t) in self.team.iter_mut().enumerate().filter(|(_, t)| t.is_alive() && t.is_sick()) {
+            if treated < medics {
+                t.cure_sickness();
+                treated += 1;
+                log!(info, format!("Trooper#{} is treated for sickness", ti + 1), false);
+            } else {
+                t.worsen_sickness();
+                log!(info, format!("Trooper#{}'s sickness worsens (stage {})", ti + 1, t.sickness()), false);
+            }
+        }
     }
 
     pub fn rebalance_team(&mut self, hp_factor: f32, ap_factor: f32) {
         for t in &mut self.team {
             t.stats.hp = ((t.stats.hp as f32) * hp_factor).round() as u32;
+            t.stats.max_hp = ((t.stats.max_hp as f32) * hp_factor).round() as u32;
             t.stats.ap = ((t.stats.ap as f32) * ap_factor).round() as u32;
+            t.stats.max_ap = ((t.stats.max_ap as f32) * ap_factor).round() as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trooper_with(class: TrooperClass, gear: &[&str]) -> Trooper {
+        let mut t = Trooper::new(class);
+        let weapons = Armory::create_weapons(1, class).into_boxed_slice();
+        t.loadout = Loadout::new(weapons, gear.iter().map(|g| Gear::named(g)).collect());
+        t
+    }
+
+    #[test]
+    fn attacking_breaks_a_cloak_field() {
+        let mut t = trooper_with(TrooperClass::Scout, &["Cloak Field Unit"]);
+        t.begin_turn();
+        let activation = t.use_gear().unwrap();
+        t.apply_effect(activation.effect);
+        assert!(t.is_cloaked());
+        assert!(t.break_cloak());
+        assert!(!t.is_cloaked());
+    }
+
+    #[test]
+    fn smoke_holds_through_an_attack() {
+        let mut t = trooper_with(TrooperClass::Scout, &["Smoke Bomb"]);
+        t.apply_effect(Effect::Cloak { turns: 1 });
+        assert!(!t.break_cloak());
+        assert!(t.is_cloaked());
+    }
+
+    #[test]
+    fn warning_gear_leaves_the_equipped_slot_alone() {
+        let mut t = trooper_with(TrooperClass::Scout, &["Frag Grenade", "Sensor Node"]);
+        t.begin_turn();
+        let activation = t.use_warning_gear().unwrap();
+        assert_eq!(activation.name, "Sensor Node");
+        assert_eq!(t.loadout.equipped_gear_slot(), 0);
+    }
+
+    fn grenadiers() -> Commander {
+        let team = (0..2).map(|_| trooper_with(TrooperClass::Heavy, &["Frag Grenade"])).collect();
+        Commander { team }
+    }
+
+    #[test]
+    fn resupply_moves_charges_instead_of_minting_them() {
+        let mut squad = grenadiers();
+        squad.team[0].hand_over(4);
+        let total = |c: &Commander| c.team.iter().map(|t| t.spare_charges()).sum::<u32>();
+        let before = total(&squad);
+        assert_eq!(squad.resupply(0, 2), vec![(1, 2)]);
+        assert_eq!(squad.team[0].spare_charges(), 4);
+        assert_eq!(squad.team[1].spare_charges(), 4);
+        assert_eq!(total(&squad), before);
+    }
+
+    #[test]
+    fn resupply_needs_a_squadmate_with_charges_to_spare() {
+        let mut squad = grenadiers();
+        for t in squad.team.iter_mut() { t.hand_over(4); }
+        assert!(squad.resupply(0, 2).is_empty());
+        assert_eq!(squad.team[0].spare_charges(), 2);
+    }
+
+    fn armored(ap: u32) -> Trooper {
+        let mut t = trooper_with(TrooperClass::Heavy, &[]);
+        t.stats.max_ap = 50;
+        t.stats.ap = ap;
+        t
+    }
+
+    #[test]
+    fn armor_debuff_wears_off() {
+        let mut t = armored(30);
+        t.apply_effect(Effect::BuffAP { ap: -10, turns: 1, area: None });
+        assert_eq!(t.stats.ap, 20);
+        t.tick_effects();
+        assert_eq!(t.stats.ap, 30);
+    }
+
+    #[test]
+    fn armor_buff_caps_at_max_and_only_takes_back_what_it_gave() {
+        let mut t = armored(45);
+        t.apply_effect(Effect::BuffAP { ap: 25, turns: 1, area: None });
+        assert_eq!(t.stats.ap, 50);
+        t.tick_effects();
+        assert_eq!(t.stats.ap, 45);
+    }
+
+    #[test]
+    fn worn_effects_last_until_the_gear_is_lost() {
+        let mut t = trooper_with(TrooperClass::Medic, &["Nano-Patch"]);
+        t.equip_passives(&mut Vec::new());
+        let regen = |t: &Trooper| t.has_effect(|e| matches!(e, Effect::Regen { .. }));
+        for _ in 0..150 { t.tick_effects(); }
+        assert!(regen(&t));
+        t.destroy_gear(0);
+        assert!(!regen(&t));
+    }
+
+    fn downed() -> Trooper {
+        let mut t = trooper_with(TrooperClass::Heavy, &[]);
+        t.r#trait.hardy = false;
+        t.flaw.glass_jaw = false;
+        t.flaw.slow_recovery = false;
+        let hp = t.hp();
+        t.lose_hp(hp);
+        t
+    }
+
+    #[test]
+    fn downed_trooper_bleeds_out_on_a_timer() {
+        let mut t = downed();
+        let turns = t.bleed_out_turns();
+        assert_eq!(t.vitals(), Vitals::Downed { turns_left: turns, stabilized: false });
+        for _ in 1..turns {
+            t.bleed_out();
+            assert!(t.is_downed());
         }
+        t.bleed_out();
+        assert!(!t.is_alive());
+        assert!(!t.revive(10));
+    }
+
+    #[test]
+    fn stabilized_trooper_holds_until_revived() {
+        let mut t = downed();
+        assert!(t.stabilize());
+        for _ in 0..10 { t.bleed_out(); }
+        assert!(t.is_stabilized());
+        assert!(t.revive(10));
+        assert!(t.is_up());
+        assert_eq!(t.hp(), 10);
+    }
+
+    #[test]
+    fn slow_recovery_dulls_a_revive() {
+        let mut t = downed();
+        t.flaw.slow_recovery = true;
+        assert!(t.revive(10));
+        assert_eq!(t.hp(), 6);
+    }
+
+    #[test]
+    fn drowning_ignores_stabilization() {
+        let mut t = downed();
+        t.stabilize();
+        t.drown();
+        assert_eq!(t.vitals(), Vitals::Downed { turns_left: BLEED_OUT_TURNS - 2, stabilized: false });
+    }
+
+    #[test]
+    fn any_hit_finishes_a_downed_trooper() {
+        let mut t = downed();
+        t.take_hit(&DamageEvent::new(DamageProfile::true_damage(DamageType::Physical, 1), DamageSource::Effect));
+        assert!(!t.is_alive());
     }
 }
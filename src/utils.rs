@@ -37,7 +37,7 @@ impl RandBools {
     pub fn roll_bools<R: Rng>(pool: &mut Vec<&mut bool>, rng: &mut R, max_assign: usize, prob: f32, guaranteed_one: bool) {
         pool.shuffle(rng);
         let mut assigned = 0;
-        for (_, item) in pool.into_iter().enumerate() {
+        for item in pool.iter_mut() {
             if assigned >= max_assign { break; }
             if Self::rand_bool(prob) || (guaranteed_one && assigned == 0) {
                 **item = true;
@@ -68,7 +68,7 @@ pub struct RngHub {
 impl RngHub {
     /// Create with a specific seed (reproducible) or None to auto-seed.
     pub fn new(master_seed: Option<u64>) -> Self {
-        let master = master_seed.unwrap_or_else(|| rand::random::<u64>());
+        let master = master_seed.unwrap_or_else(rand::random::<u64>);
 
         Self { master_seed: master }
     }